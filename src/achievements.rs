@@ -0,0 +1,99 @@
+//! Goals reached within a single game, which are kept once earned. Like `game`, this knows
+//! nothing about macroquad: what's earned is worked out from the `GameEvent`s each update
+//! returns, and from the game so far.
+
+use crate::game::{GameEvent, PlayState};
+
+const HUNGRY_FRUIT: u32 = 10;
+const SURVIVOR_SECONDS: f32 = 120.0;
+const COMBO_MASTER_COMBO: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    /// Eat `HUNGRY_FRUIT` fruit in one game.
+    Hungry,
+    /// Still be going after `SURVIVOR_SECONDS`.
+    Survivor,
+    /// Fill the board, or win some other way.
+    CleanSweep,
+    /// Build a combo of `COMBO_MASTER_COMBO`.
+    ComboMaster,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::Hungry,
+        Achievement::Survivor,
+        Achievement::CleanSweep,
+        Achievement::ComboMaster,
+    ];
+
+    /// What it's saved as.
+    pub fn key(self) -> &'static str {
+        match self {
+            Achievement::Hungry => "hungry",
+            Achievement::Survivor => "survivor",
+            Achievement::CleanSweep => "clean_sweep",
+            Achievement::ComboMaster => "combo_master",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Achievement> {
+        Achievement::ALL
+            .into_iter()
+            .find(|achievement| achievement.key() == key)
+    }
+
+    /// Whether `state`, which `events` just happened in, has earned this.
+    pub fn is_earned(self, state: &PlayState, events: &[GameEvent]) -> bool {
+        let ate = events.contains(&GameEvent::Ate);
+        match self {
+            Achievement::Hungry => ate && state.fruit_eaten >= HUNGRY_FRUIT,
+            Achievement::Survivor => state.elapsed >= SURVIVOR_SECONDS && !state.is_dead(),
+            Achievement::CleanSweep => state.won,
+            Achievement::ComboMaster => ate && state.combo >= COMBO_MASTER_COMBO,
+        }
+    }
+}
+
+/// The achievements not in `earned` that `state` has now earned, in the order they're listed.
+pub fn newly_earned(
+    earned: &[Achievement],
+    state: &PlayState,
+    events: &[GameEvent],
+) -> Vec<Achievement> {
+    Achievement::ALL
+        .into_iter()
+        .filter(|achievement| !earned.contains(achievement))
+        .filter(|achievement| achievement.is_earned(state, events))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{reset_state, Config};
+
+    #[test]
+    fn achievements_are_earned_once() {
+        let mut state = reset_state(&Config::default(), 0);
+        state.fruit_eaten = HUNGRY_FRUIT;
+        state.combo = COMBO_MASTER_COMBO;
+
+        // Only eating the fruit that does it counts.
+        assert!(newly_earned(&[], &state, &[]).is_empty());
+        let events = [GameEvent::Ate];
+        assert_eq!(
+            newly_earned(&[], &state, &events),
+            vec![Achievement::Hungry, Achievement::ComboMaster]
+        );
+        assert_eq!(
+            newly_earned(&[Achievement::Hungry], &state, &events),
+            vec![Achievement::ComboMaster]
+        );
+
+        for achievement in Achievement::ALL {
+            assert_eq!(Achievement::from_key(achievement.key()), Some(achievement));
+        }
+    }
+}
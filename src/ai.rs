@@ -0,0 +1,336 @@
+//! An autopilot that plays the game by itself. Like `game`, it knows nothing about macroquad.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    is_opposite_of, next_position, Config, Direction, FruitKind, Layout, PlayState, Position,
+    WrapMode,
+};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
+];
+
+/// The Hamiltonian autopilot only cuts corners while the snake is shorter than this fraction
+/// of the board. Past that, shortcuts are too likely to trap it.
+const SHORTCUT_MAX_FILL: f32 = 0.5;
+/// How much room a shortcut must leave before the tail. A golden fruit grows the snake by
+/// two, which would otherwise let it run into its own tail.
+const SHORTCUT_TAIL_MARGIN: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiStrategy {
+    /// Chase the nearest fruit along the shortest path.
+    Bfs,
+    /// Follow a cycle through every open cell, which can never trap the snake.
+    Hamiltonian,
+}
+
+impl AiStrategy {
+    pub fn next(self) -> AiStrategy {
+        match self {
+            AiStrategy::Bfs => AiStrategy::Hamiltonian,
+            AiStrategy::Hamiltonian => AiStrategy::Bfs,
+        }
+    }
+}
+
+/// The cells the snake must not move into next tick.
+struct Obstacles {
+    width: i32,
+    height: i32,
+    blocked: Vec<bool>,
+}
+
+impl Obstacles {
+    fn new(state: &PlayState, config: &Config) -> Obstacles {
+        let width = config.board_width as i32;
+        let height = config.board_height as i32;
+        let mut obstacles = Obstacles {
+            width,
+            height,
+            blocked: vec![false; (width * height) as usize],
+        };
+
+        // The tail is left out, because it moves out of the way as the head moves in. Any other
+        // snake is in the way from head to tail.
+        let parts = &state.snakes[0].parts;
+        let body = &parts[..parts.len() - 1];
+        let others = state.snakes[1..].iter().flat_map(|snake| &snake.parts);
+        let moving_walls = state.moving_walls.iter().map(|wall| &wall.position);
+        let poison = state
+            .fruit
+            .iter()
+            .filter(|(_, kind)| *kind == FruitKind::Poison)
+            .map(|(poison, _)| poison);
+        for cell in state
+            .walls
+            .iter()
+            .chain(body)
+            .chain(others)
+            .chain(moving_walls)
+            .chain(poison)
+        {
+            if let Some(index) = obstacles.index(*cell) {
+                obstacles.blocked[index] = true;
+            }
+        }
+
+        obstacles
+    }
+
+    /// Where `cell` lives in `blocked`, or `None` if it is off the board.
+    fn index(&self, Position((x, y)): Position) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+
+    fn is_blocked(&self, cell: Position) -> bool {
+        self.index(cell).is_none_or(|index| self.blocked[index])
+    }
+}
+
+/// The direction to take next: the first step along a shortest path to the nearest fruit,
+/// found by a breadth-first search. When no fruit can be reached, it settles for any move
+/// that doesn't die straight away.
+pub fn bfs_direction(state: &PlayState, config: &Config) -> Direction {
+    let obstacles = Obstacles::new(state, config);
+    let is_target = |cell: Position| {
+        state
+            .fruit
+            .iter()
+            .any(|(fruit, kind)| *kind == FruitKind::Normal && *fruit == cell)
+            || matches!(state.golden_fruit, Some((golden, _)) if golden == cell)
+    };
+
+    // For every cell reached so far, the move out of the head that the path to it starts with.
+    let mut first_steps = vec![None; obstacles.blocked.len()];
+    let mut frontier = VecDeque::new();
+    for direction in DIRECTIONS {
+        if is_opposite_of(direction, state.snakes[0].direction) {
+            continue;
+        }
+        let cell = next_position(state.snakes[0].parts[0], direction, config);
+        if obstacles.is_blocked(cell) {
+            continue;
+        }
+        if is_target(cell) {
+            return direction;
+        }
+        if let Some(index) = obstacles.index(cell) {
+            first_steps[index] = Some(direction);
+            frontier.push_back((cell, direction));
+        }
+    }
+
+    while let Some((cell, first_step)) = frontier.pop_front() {
+        for direction in DIRECTIONS {
+            let next = next_position(cell, direction, config);
+            let index = match obstacles.index(next) {
+                Some(index) if !obstacles.blocked[index] && first_steps[index].is_none() => index,
+                _ => continue,
+            };
+            if is_target(next) {
+                return first_step;
+            }
+            first_steps[index] = Some(first_step);
+            frontier.push_back((next, first_step));
+        }
+    }
+
+    safe_direction(state, config, &obstacles)
+}
+
+/// Carries on straight if that's safe, otherwise turns towards any free cell. If every way is
+/// blocked there's nothing to be done, so it carries on straight anyway.
+fn safe_direction(state: &PlayState, config: &Config, obstacles: &Obstacles) -> Direction {
+    let head = state.snakes[0].parts[0];
+    std::iter::once(state.snakes[0].direction)
+        .chain(DIRECTIONS)
+        .filter(|direction| !is_opposite_of(*direction, state.snakes[0].direction))
+        .find(|direction| !obstacles.is_blocked(next_position(head, *direction, config)))
+        .unwrap_or(state.snakes[0].direction)
+}
+
+/// A route that visits every open cell exactly once before returning to its start.
+pub struct HamiltonianCycle {
+    width: u32,
+    height: u32,
+    /// The cells in the order they are visited. Empty if there is no cycle on this board.
+    order: Vec<Position>,
+    /// For each cell on the board, where it is in `order`.
+    indices: Vec<usize>,
+}
+
+impl HamiltonianCycle {
+    /// Builds a cycle over the open cells of the board described by `config`.
+    ///
+    /// It's only attempted on boards without interior walls, so never on a custom level. The
+    /// cells form a grid, and a grid with an odd number of cells has no Hamiltonian cycle, so it
+    /// takes an even side too. On any other board the cycle comes out empty.
+    pub fn new(config: &Config) -> HamiltonianCycle {
+        let mut cycle = HamiltonianCycle {
+            width: config.board_width,
+            height: config.board_height,
+            order: Vec::new(),
+            indices: vec![0; (config.board_width * config.board_height) as usize],
+        };
+
+        let (width, height) = match config.wrap_mode() {
+            WrapMode::Walls => (
+                config.board_width as i32 - 2,
+                config.board_height as i32 - 2,
+            ),
+            WrapMode::Wrap => (config.board_width as i32, config.board_height as i32),
+        };
+        if config.layout != Layout::Empty
+            || config.custom_level.is_some()
+            || width < 2
+            || height < 2
+        {
+            return cycle;
+        }
+
+        // Lay it out in coordinates relative to the region being covered: up the first column,
+        // snaking down and up through the remaining columns without touching the first row,
+        // then back home along the first row. Snaking down-up has to finish on the way down
+        // for that to work, which needs an even number of columns, so swap axes if needed.
+        let transposed = width % 2 != 0;
+        let (columns, rows) = if transposed {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        if columns % 2 != 0 {
+            return cycle;
+        }
+
+        let mut order = Vec::new();
+        for row in 0..rows {
+            order.push((0, row));
+        }
+        for column in 1..columns {
+            if column % 2 == 1 {
+                for row in (1..rows).rev() {
+                    order.push((column, row));
+                }
+            } else {
+                for row in 1..rows {
+                    order.push((column, row));
+                }
+            }
+        }
+        for column in (1..columns).rev() {
+            order.push((column, 0));
+        }
+
+        // The snake starts heading east out of (1, 1), so the cycle has to as well. Transposed,
+        // it already does. Otherwise, run it backwards from the same start.
+        if !transposed {
+            order.reverse();
+            order.rotate_right(1);
+        }
+
+        // The region starts at (1, 1): inside the border with walls, and where the snake
+        // starts with wrap-around.
+        for (column, row) in order {
+            let (x, y) = if transposed {
+                (row, column)
+            } else {
+                (column, row)
+            };
+            let (x, y) = match config.wrap_mode() {
+                WrapMode::Walls => (x + 1, y + 1),
+                WrapMode::Wrap => (
+                    (x + 1) % config.board_width as i32,
+                    (y + 1) % config.board_height as i32,
+                ),
+            };
+            cycle.order.push(Position((x, y)));
+        }
+        for (index, Position((x, y))) in cycle.order.iter().cloned().enumerate() {
+            cycle.indices[(y * config.board_width as i32 + x) as usize] = index;
+        }
+
+        cycle
+    }
+
+    /// Whether the cycle was built for a board the size of the one in `config`.
+    pub fn fits(&self, config: &Config) -> bool {
+        self.width == config.board_width && self.height == config.board_height
+    }
+
+    fn index(&self, Position((x, y)): Position) -> usize {
+        self.indices[(y * self.width as i32 + x) as usize]
+    }
+
+    /// How many steps along the cycle it takes to get from `from` to `to`.
+    fn distance(&self, from: Position, to: Position) -> usize {
+        (self.index(to) + self.order.len() - self.index(from)) % self.order.len()
+    }
+}
+
+/// The direction to take next when following `cycle`. While the snake is short, it skips
+/// ahead along the cycle towards the fruit, so long as it can't overtake its own tail. Falls
+/// back to `bfs_direction` on boards with no cycle, and to `safe_direction` if something is in
+/// the way of the next cell along it.
+pub fn hamiltonian_direction(
+    state: &PlayState,
+    config: &Config,
+    cycle: &HamiltonianCycle,
+) -> Direction {
+    if cycle.order.is_empty() {
+        return bfs_direction(state, config);
+    }
+
+    let head = state.snakes[0].parts[0];
+    let tail = state.snakes[0].parts[state.snakes[0].parts.len() - 1];
+    let successor = cycle.order[(cycle.index(head) + 1) % cycle.order.len()];
+
+    let obstacles = Obstacles::new(state, config);
+    if obstacles.is_blocked(successor) {
+        return safe_direction(state, config, &obstacles);
+    }
+    let mut best = DIRECTIONS
+        .iter()
+        .cloned()
+        .find(|direction| next_position(head, *direction, config) == successor)
+        .unwrap_or(state.snakes[0].direction);
+
+    let fill = state.snakes[0].parts.len() as f32 / cycle.order.len() as f32;
+    if fill < SHORTCUT_MAX_FILL {
+        let fruit_distance = state
+            .fruit
+            .iter()
+            .filter(|(_, kind)| *kind == FruitKind::Normal)
+            .map(|(fruit, _)| cycle.distance(head, *fruit))
+            .min()
+            .unwrap_or(1);
+        let room = cycle
+            .distance(head, tail)
+            .saturating_sub(SHORTCUT_TAIL_MARGIN);
+
+        let mut best_distance = 1;
+        for direction in DIRECTIONS {
+            let cell = next_position(head, direction, config);
+            if obstacles.is_blocked(cell) {
+                continue;
+            }
+            let distance = cycle.distance(head, cell);
+            if distance > best_distance && distance <= fruit_distance && distance < room {
+                best = direction;
+                best_distance = distance;
+            }
+        }
+    }
+
+    best
+}
@@ -0,0 +1,96 @@
+//! Overrides for a single run, given on the command line, e.g.
+//! `snake --width 20 --height 15 --speed 0.1 --wrap`. They win over `settings.toml`.
+
+use std::process;
+
+use snake::game::{Config, WrapMode};
+
+use crate::settings::{MIN_BOARD_HEIGHT, MIN_BOARD_WIDTH};
+
+const USAGE: &str = "\
+Usage: snake [options]
+
+Options:
+  --width <cells>      how many cells across the board is
+  --height <cells>     how many cells high the board is
+  --speed <seconds>    seconds between each move of the snake
+  --wrap               leave one edge of the board to come in the opposite one
+  --walls              border the board with walls
+  --help               show this and quit";
+
+/// What was asked for on the command line. Anything left out keeps its setting.
+#[derive(Default)]
+pub struct Args {
+    width: Option<u32>,
+    height: Option<u32>,
+    speed: Option<f32>,
+    wrap_mode: Option<WrapMode>,
+}
+
+impl Args {
+    /// Reads the command line. Anything it can't make sense of prints how to use it, and
+    /// quits, as does `--help`.
+    pub fn parse_or_exit() -> Args {
+        match Args::parse(std::env::args().skip(1)) {
+            Ok(Some(args)) => args,
+            Ok(None) => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            Err(err) => {
+                eprintln!("{}\n\n{}", err, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    /// Returns `None` if `--help` was asked for.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--width" => parsed.width = Some(cells(&arg, &value()?, MIN_BOARD_WIDTH)?),
+                "--height" => parsed.height = Some(cells(&arg, &value()?, MIN_BOARD_HEIGHT)?),
+                "--speed" => {
+                    let value = value()?;
+                    parsed.speed = match value.parse::<f32>() {
+                        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Some(seconds),
+                        _ => return Err(format!("{} must be more than zero, not {}", arg, value)),
+                    };
+                }
+                "--wrap" => parsed.wrap_mode = Some(WrapMode::Wrap),
+                "--walls" => parsed.wrap_mode = Some(WrapMode::Walls),
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(Some(parsed))
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(width) = self.width {
+            config.board_width = width;
+        }
+        if let Some(height) = self.height {
+            config.board_height = height;
+        }
+        if let Some(speed) = self.speed {
+            config.tick_seconds = speed;
+        }
+        if let Some(wrap_mode) = self.wrap_mode {
+            config.wrap_mode = wrap_mode;
+        }
+    }
+}
+
+/// Reads `value`, given for `arg`, as a number of cells no fewer than `min`.
+fn cells(arg: &str, value: &str, min: u32) -> Result<u32, String> {
+    match value.parse() {
+        Ok(cells) if cells >= min => Ok(cells),
+        _ => Err(format!(
+            "{} must be a whole number of at least {}, not {}",
+            arg, min, value
+        )),
+    }
+}
@@ -0,0 +1,386 @@
+//! Which keys do what. The defaults can be changed by listing keys in `keys.txt`, next to the
+//! high score file, one action per line:
+//!
+//! ```text
+//! up = Up I
+//! pause = P
+//! ```
+//!
+//! Key names are macroquad's `KeyCode` names, such as `A`, `Key1`, `Space` or `LeftShift`.
+
+use std::{fs, path::PathBuf};
+
+use macroquad::input::utils;
+use macroquad::miniquad::{EventHandler, KeyMods};
+use macroquad::prelude::*;
+use snake::game::Direction;
+use snake::lang::{Lang, Text};
+
+/// Every key that can be named in `keys.txt`.
+const NAMEABLE_KEYS: [KeyCode; 70] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+    KeyCode::Equal,
+    KeyCode::Minus,
+    KeyCode::KpAdd,
+    KeyCode::KpSubtract,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+];
+
+/// The keys for each action. Any one of an action's keys triggers it.
+pub struct KeyBindings {
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    /// Player two's snake, in a two player game. With one player, these steer the snake too.
+    pub second_up: Vec<KeyCode>,
+    pub second_down: Vec<KeyCode>,
+    pub second_left: Vec<KeyCode>,
+    pub second_right: Vec<KeyCode>,
+    pub pause: Vec<KeyCode>,
+    /// Takes the next step straight away, without waiting for the tick.
+    pub fast_drop: Vec<KeyCode>,
+    /// Leaves the game over screen, to start again from the menu.
+    pub restart: Vec<KeyCode>,
+    /// Exits the game, from the menu or while paused.
+    pub quit: Vec<KeyCode>,
+    /// Hands the snake over to the autopilot, or takes it back.
+    pub autopilot: Vec<KeyCode>,
+    /// Nudge the snake's speed up or down during a game.
+    pub faster: Vec<KeyCode>,
+    pub slower: Vec<KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            up: vec![KeyCode::Up],
+            down: vec![KeyCode::Down],
+            left: vec![KeyCode::Left],
+            right: vec![KeyCode::Right],
+            second_up: vec![KeyCode::W],
+            second_down: vec![KeyCode::S],
+            second_left: vec![KeyCode::A],
+            second_right: vec![KeyCode::D],
+            pause: vec![KeyCode::Space],
+            fast_drop: vec![KeyCode::LeftShift, KeyCode::RightShift],
+            restart: vec![KeyCode::Escape],
+            quit: vec![KeyCode::Q],
+            autopilot: vec![KeyCode::P],
+            faster: vec![KeyCode::Equal, KeyCode::KpAdd],
+            slower: vec![KeyCode::Minus, KeyCode::KpSubtract],
+        }
+    }
+}
+
+impl KeyBindings {
+    fn path() -> PathBuf {
+        crate::data_dir().join("keys.txt")
+    }
+
+    /// The default bindings, with any changes from `keys.txt` applied. Lines that can't be
+    /// understood are reported and skipped, leaving that action as it was.
+    pub fn load_from_disk() -> KeyBindings {
+        let mut bindings = KeyBindings::default();
+        let Ok(contents) = fs::read_to_string(Self::path()) else {
+            return bindings;
+        };
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(err) = bindings.apply(line) {
+                eprintln!("Ignoring key binding {:?}: {}", line, err);
+            }
+        }
+
+        bindings
+    }
+
+    /// Applies a line of the form `action = Key Key ...`.
+    fn apply(&mut self, line: &str) -> Result<(), String> {
+        let (action, names) = line
+            .split_once('=')
+            .ok_or_else(|| "expected `action = keys`".to_string())?;
+        let keys = names
+            .split_whitespace()
+            .map(|name| key_from_name(name).ok_or_else(|| format!("unknown key {:?}", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if keys.is_empty() {
+            return Err("no keys given".to_string());
+        }
+
+        let binding = match action.trim() {
+            "up" => &mut self.up,
+            "down" => &mut self.down,
+            "left" => &mut self.left,
+            "right" => &mut self.right,
+            "second_up" => &mut self.second_up,
+            "second_down" => &mut self.second_down,
+            "second_left" => &mut self.second_left,
+            "second_right" => &mut self.second_right,
+            "pause" => &mut self.pause,
+            "fast_drop" => &mut self.fast_drop,
+            "restart" => &mut self.restart,
+            "quit" => &mut self.quit,
+            "autopilot" => &mut self.autopilot,
+            "faster" => &mut self.faster,
+            "slower" => &mut self.slower,
+            action => return Err(format!("unknown action {:?}", action)),
+        };
+        *binding = keys;
+
+        Ok(())
+    }
+
+    /// Turns asked for with player one's keys among `pressed`, in the order they were pressed.
+    pub fn turns(&self, pressed: &[KeyCode]) -> Vec<Direction> {
+        pressed_turns(pressed, &[self.steering()])
+    }
+
+    /// Turns asked for with player two's keys among `pressed`, in the order they were pressed.
+    pub fn second_turns(&self, pressed: &[KeyCode]) -> Vec<Direction> {
+        pressed_turns(pressed, &[self.second_steering()])
+    }
+
+    /// Turns asked for with either player's keys among `pressed`, for one player to steer by.
+    pub fn either_turns(&self, pressed: &[KeyCode]) -> Vec<Direction> {
+        pressed_turns(pressed, &[self.steering(), self.second_steering()])
+    }
+
+    /// Player one's keys for the north, south, west and east turns.
+    fn steering(&self) -> [&Vec<KeyCode>; 4] {
+        [&self.up, &self.down, &self.left, &self.right]
+    }
+
+    fn second_steering(&self) -> [&Vec<KeyCode>; 4] {
+        [
+            &self.second_up,
+            &self.second_down,
+            &self.second_left,
+            &self.second_right,
+        ]
+    }
+}
+
+/// The turns for each of `pressed` that's one of the north, south, west or east keys of any of
+/// `steering`, in order.
+fn pressed_turns(pressed: &[KeyCode], steering: &[[&Vec<KeyCode>; 4]]) -> Vec<Direction> {
+    let directions = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+    pressed
+        .iter()
+        .filter_map(|key| {
+            steering.iter().find_map(|keys| {
+                keys.iter()
+                    .zip(directions)
+                    .find(|(keys, _)| keys.contains(key))
+                    .map(|(_, direction)| direction)
+            })
+        })
+        .collect()
+}
+
+/// Every key pressed since the last frame, in the order it was pressed.
+///
+/// `is_key_pressed` only says whether a key went down at all during the frame. On a slow
+/// machine, two quick turns can land in the same frame, and this keeps which came first, and
+/// both taps when it's the same key twice.
+pub struct KeyPresses {
+    subscriber: usize,
+    /// Whether a held key's repeats count as more presses of it.
+    repeats: bool,
+}
+
+impl KeyPresses {
+    pub fn new(repeats: bool) -> KeyPresses {
+        KeyPresses {
+            subscriber: utils::register_input_subscriber(),
+            repeats,
+        }
+    }
+
+    /// The keys pressed since this was last called. It must be called every frame, or
+    /// presses pile up to be taken all at once later.
+    pub fn take(&self) -> Vec<KeyCode> {
+        let mut collector = PressCollector {
+            repeats: self.repeats,
+            pressed: Vec::new(),
+        };
+        utils::repeat_all_miniquad_input(&mut collector, self.subscriber);
+        collector.pressed
+    }
+}
+
+/// Gathers the key presses out of macroquad's record of input events.
+struct PressCollector {
+    repeats: bool,
+    pressed: Vec<KeyCode>,
+}
+
+impl EventHandler for PressCollector {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn key_down_event(&mut self, keycode: KeyCode, _keymods: KeyMods, repeat: bool) {
+        if !repeat || self.repeats {
+            self.pressed.push(keycode);
+        }
+    }
+}
+
+/// Whether any of `keys` went down this frame.
+pub fn is_pressed(keys: &[KeyCode]) -> bool {
+    keys.iter().any(|key| is_key_pressed(*key))
+}
+
+/// Whether any of `keys` is being held down.
+pub fn is_down(keys: &[KeyCode]) -> bool {
+    keys.iter().any(|key| is_key_down(*key))
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    NAMEABLE_KEYS
+        .iter()
+        .cloned()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))
+}
+
+/// The on-screen instructions, worded for whatever the keys are bound to. Formatted once, up
+/// front, so `render` doesn't allocate every frame.
+pub struct KeyHints {
+    pub steer: String,
+    /// `steer`, for a two player game, where each player has their own keys.
+    pub steer_players: String,
+    pub pause: String,
+    pub fast_drop: String,
+    pub resume: String,
+    pub restart: String,
+    pub quit: String,
+    pub confirm_quit: String,
+    pub nudge_speed: String,
+    pub autopilot_on: String,
+    pub autopilot_off: String,
+}
+
+impl KeyHints {
+    pub fn new(bindings: &KeyBindings, lang: Lang) -> KeyHints {
+        let first = steer_names([
+            &bindings.up,
+            &bindings.left,
+            &bindings.down,
+            &bindings.right,
+        ]);
+        let second = steer_names([
+            &bindings.second_up,
+            &bindings.second_left,
+            &bindings.second_down,
+            &bindings.second_right,
+        ]);
+
+        let hint = |text, keys: &[KeyCode]| lang.fill(text, &[&key_names(keys)]);
+
+        KeyHints {
+            steer: lang.fill(Text::Steer, &[&first, &second]),
+            steer_players: lang.fill(Text::SteerPlayers, &[&first, &second]),
+            pause: hint(Text::Pause, &bindings.pause),
+            fast_drop: hint(Text::FastDrop, &bindings.fast_drop),
+            resume: hint(Text::Resume, &bindings.pause),
+            restart: hint(Text::Restart, &bindings.restart),
+            quit: hint(Text::Quit, &bindings.quit),
+            confirm_quit: hint(Text::ConfirmQuit, &bindings.quit),
+            nudge_speed: lang.fill(
+                Text::NudgeSpeed,
+                &[&key_names(&bindings.faster), &key_names(&bindings.slower)],
+            ),
+            autopilot_on: hint(Text::AutopilotOn, &bindings.autopilot),
+            autopilot_off: hint(Text::AutopilotOff, &bindings.autopilot),
+        }
+    }
+}
+
+/// "'Up', 'Left', 'Down', 'Right'", for the keys that steer one snake.
+fn steer_names(keys: [&Vec<KeyCode>; 4]) -> String {
+    keys.iter()
+        .map(|keys| key_names(keys))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "'Up'/'W'", for showing the player which keys to press.
+fn key_names(keys: &[KeyCode]) -> String {
+    keys.iter()
+        .map(|key| format!("'{:?}'", key))
+        .collect::<Vec<_>>()
+        .join("/")
+}
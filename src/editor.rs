@@ -0,0 +1,125 @@
+//! Drawing levels with the mouse, and saving them to be played. See `game::parse_level` for
+//! what ends up in the file.
+
+use std::{fs, path::PathBuf};
+
+use snake::game::{self, Config, CustomLevel, Position, Tile};
+use snake::lang::{Lang, Text};
+
+pub struct Editor {
+    /// Indexed `[y][x]`, with y pointing up like the board.
+    tiles: Vec<Vec<Tile>>,
+    pub brush: Tile,
+    /// Where the level is saved, relative to `settings.toml` unless it's absolute.
+    path: PathBuf,
+    /// "Press 'S' to save the level to ...", formatted once for `path`.
+    pub save_label: String,
+    /// How the last save went, if there has been one.
+    pub status: String,
+}
+
+impl Editor {
+    /// Starts from the board being played on, if it was drawn, or else an empty walled board
+    /// the size of `config`'s.
+    pub fn new(config: &Config, path: PathBuf) -> Editor {
+        let (width, height) = (config.board_width as i32, config.board_height as i32);
+        let mut tiles = vec![vec![Tile::Empty; width as usize]; height as usize];
+        match &config.custom_level {
+            Some(level) => {
+                for Position((x, y)) in level.walls.iter().cloned() {
+                    tiles[y as usize][x as usize] = Tile::Wall;
+                }
+                for Position((x, y)) in level.fruit_spawns.iter().cloned() {
+                    tiles[y as usize][x as usize] = Tile::Fruit;
+                }
+                let Position((x, y)) = level.start;
+                tiles[y as usize][x as usize] = Tile::Start;
+            }
+            None => {
+                for (y, row) in tiles.iter_mut().enumerate() {
+                    for (x, tile) in row.iter_mut().enumerate() {
+                        let on_edge =
+                            x == 0 || y == 0 || x as i32 == width - 1 || y as i32 == height - 1;
+                        if on_edge {
+                            *tile = Tile::Wall;
+                        }
+                    }
+                }
+            }
+        }
+
+        let save_label = config.lang.fill(
+            Text::EditorSave,
+            &[&crate::data_dir().join(&path).display()],
+        );
+        Editor {
+            tiles,
+            brush: Tile::Wall,
+            path,
+            save_label,
+            status: String::new(),
+        }
+    }
+
+    /// Every tile that isn't empty, and where it is.
+    pub fn tiles(&self) -> impl Iterator<Item = (Position, Tile)> + '_ {
+        self.tiles.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, tile)| **tile != Tile::Empty)
+                .map(move |(x, tile)| (Position((x as i32, y as i32)), *tile))
+        })
+    }
+
+    /// Paints `tile` at `x`, `y`, which must be on the board. There is only ever one start, so
+    /// painting one moves it.
+    pub fn paint(&mut self, Position((x, y)): Position, tile: Tile) {
+        if tile == Tile::Start {
+            for cell in self.tiles.iter_mut().flatten() {
+                if *cell == Tile::Start {
+                    *cell = Tile::Empty;
+                }
+            }
+        }
+        self.tiles[y as usize][x as usize] = tile;
+    }
+
+    /// The level as it's written to a file, top row first.
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for row in self.tiles.iter().rev() {
+            text.extend(row.iter().map(|tile| tile.to_char()));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Saves the level, as long as it can be played, and returns it so that it can be.
+    /// Either way, `status` says how it went.
+    pub fn save(&mut self, lang: Lang) -> Option<CustomLevel> {
+        let text = self.to_text();
+        let level = match game::parse_level(&text) {
+            Ok(level) => level,
+            Err(err) => {
+                self.status = err;
+                return None;
+            }
+        };
+
+        let path = crate::data_dir().join(&self.path);
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, text));
+        match result {
+            Ok(()) => {
+                self.status = lang.text(Text::EditorSaved).to_string();
+                Some(level)
+            }
+            Err(err) => {
+                self.status = format!("{}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,3881 @@
+//! The rules of the game: movement, collisions and fruit. Nothing in here knows about
+//! macroquad, so it can be driven (and tested) without opening a window.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai;
+use crate::lang::{Lang, Text};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+pub fn is_opposite_of(one: Direction, other: Direction) -> bool {
+    use Direction::*;
+    matches!(
+        (one, other),
+        (East, West) | (West, East) | (North, South) | (South, North)
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position(pub (i32, i32));
+
+/// What ended a game that was lost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    /// Ran into a wall, moving or not, or the other snake.
+    Wall,
+    SelfBite,
+    /// Ate poison with nothing left to lose.
+    Poison,
+    /// Ran out of time in time attack.
+    Starved,
+    /// Run into by a longer snake, with `Config::devouring`.
+    Eaten,
+}
+
+/// Something that happened during an `update`, for whatever's running the game to react to
+/// with sounds, stats and the like, without having to spot it in the state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    /// A snake ate a fruit of any kind.
+    Ate,
+    /// A snake crashed and lost a life, with more to spare.
+    Crashed(DeathCause),
+    /// A snake crashed for the last time, and is out.
+    Died(DeathCause),
+    /// The campaign moved on to its next level.
+    LevelUp,
+    PowerUp(PowerUp),
+    /// A crash was undone by spending a rewind.
+    Rewound,
+}
+
+/// A hazard that steps along a straight line, once every time the snake moves, and turns back
+/// whenever it would run into a wall or another hazard. Touching one is a crash.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovingWall {
+    pub position: Position,
+    pub direction: Direction,
+}
+
+pub fn next_position(from: Position, direction: Direction, config: &Config) -> Position {
+    let Position((old_x, old_y)) = from;
+
+    let (dx, dy) = match direction {
+        Direction::North => (old_x, old_y + 1),
+        Direction::South => (old_x, old_y - 1),
+        Direction::West => (old_x - 1, old_y),
+        Direction::East => (old_x + 1, old_y),
+    };
+
+    match config.wrap_mode() {
+        WrapMode::Walls => Position((dx, dy)),
+        WrapMode::Wrap => Position((
+            dx.rem_euclid(config.board_width as i32),
+            dy.rem_euclid(config.board_height as i32),
+        )),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WrapMode {
+    /// The board is bordered by walls that kill the snake.
+    Walls,
+    /// There is no border; leaving one edge of the board enters from the opposite edge.
+    Wrap,
+}
+
+/// A small SplitMix64 generator. It is seeded per game rather than shared globally, so that
+/// a seed always reproduces the same run.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `low..high`.
+    pub fn gen_range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next_u64() % (high - low) as u64) as usize
+    }
+
+    /// Returns an index into `weights`, each picked in proportion to its weight. With no
+    /// weight at all, it's always the first.
+    pub fn pick_weighted(&mut self, weights: &[u32]) -> usize {
+        let total: u64 = weights.iter().map(|&weight| weight as u64).sum();
+        if total == 0 {
+            return 0;
+        }
+        let mut picked = self.next_u64() % total;
+        for (index, &weight) in weights.iter().enumerate() {
+            if picked < weight as u64 {
+                return index;
+            }
+            picked -= weight as u64;
+        }
+        unreachable!("the pick is always under the total")
+    }
+}
+
+/// Interior walls placed on top of the border.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    Empty,
+    /// A plus sign through the middle of the board.
+    Cross,
+    /// A grid of single-cell pillars.
+    Pillars,
+    /// `Config::random_wall_count` walls scattered anywhere that keeps the board connected.
+    Random,
+}
+
+impl Layout {
+    pub fn next(self) -> Layout {
+        match self {
+            Layout::Empty => Layout::Cross,
+            Layout::Cross => Layout::Pillars,
+            Layout::Pillars => Layout::Random,
+            Layout::Random => Layout::Empty,
+        }
+    }
+}
+
+/// How hard a game is. Each step scales the speed it starts at, how quickly it speeds up and
+/// how many walls `Layout::Random` scatters, from what the rest of `Config` asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn easier(self) -> Difficulty {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => Difficulty::Easy,
+            Difficulty::Hard => Difficulty::Normal,
+        }
+    }
+
+    pub fn harder(self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal | Difficulty::Hard => Difficulty::Hard,
+        }
+    }
+
+    /// The tick interval a game starts at, for one that asks for `tick_seconds`.
+    fn starting_tick(self, tick_seconds: f32) -> f32 {
+        match self {
+            Difficulty::Easy => tick_seconds * 1.25,
+            Difficulty::Normal => tick_seconds,
+            Difficulty::Hard => tick_seconds * 0.75,
+        }
+    }
+
+    /// What each fruit eaten multiplies the tick interval by.
+    fn speed_ramp(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.97,
+            Difficulty::Normal => SPEED_RAMP,
+            Difficulty::Hard => 0.92,
+        }
+    }
+
+    /// How many walls `Layout::Random` scatters, for a game that asks for `random_wall_count`.
+    fn wall_count(self, random_wall_count: u32) -> u32 {
+        match self {
+            Difficulty::Easy => random_wall_count / 2,
+            Difficulty::Normal => random_wall_count,
+            Difficulty::Hard => random_wall_count * 3 / 2,
+        }
+    }
+}
+
+/// One stage of the campaign. Eating `fruit_quota` fruit moves on to the next.
+pub struct Level {
+    pub layout: Layout,
+    pub random_wall_count: u32,
+    pub moving_walls: u32,
+    pub tick_seconds: f32,
+    pub fruit_quota: u32,
+}
+
+/// The campaign, which gets busier and faster as it goes. Clearing the last level wins.
+pub const LEVELS: [Level; 5] = [
+    Level {
+        layout: Layout::Empty,
+        random_wall_count: 0,
+        moving_walls: 0,
+        tick_seconds: 0.2,
+        fruit_quota: 5,
+    },
+    Level {
+        layout: Layout::Pillars,
+        random_wall_count: 0,
+        moving_walls: 0,
+        tick_seconds: 0.18,
+        fruit_quota: 8,
+    },
+    Level {
+        layout: Layout::Cross,
+        random_wall_count: 0,
+        moving_walls: 1,
+        tick_seconds: 0.16,
+        fruit_quota: 10,
+    },
+    Level {
+        layout: Layout::Random,
+        random_wall_count: 8,
+        moving_walls: 1,
+        tick_seconds: 0.14,
+        fruit_quota: 12,
+    },
+    Level {
+        layout: Layout::Random,
+        random_wall_count: 14,
+        moving_walls: 2,
+        tick_seconds: 0.12,
+        fruit_quota: 15,
+    },
+];
+
+/// A board drawn by hand, read by `parse_level`, that replaces the usual walls and start.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CustomLevel {
+    pub width: u32,
+    pub height: u32,
+    pub walls: Vec<Position>,
+    /// Where the snake's head starts, heading east with its tail just behind.
+    pub start: Position,
+    /// The only cells fruit appears on, while any of them are free. Anywhere, if there are none.
+    pub fruit_spawns: Vec<Position>,
+}
+
+/// What a cell of a `CustomLevel` is drawn as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Start,
+    Fruit,
+}
+
+impl Tile {
+    pub fn from_char(tile: char) -> Option<Tile> {
+        match tile {
+            '.' => Some(Tile::Empty),
+            '#' => Some(Tile::Wall),
+            'S' => Some(Tile::Start),
+            'F' => Some(Tile::Fruit),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::Wall => '#',
+            Tile::Start => 'S',
+            Tile::Fruit => 'F',
+        }
+    }
+
+    pub fn next(self) -> Tile {
+        match self {
+            Tile::Wall => Tile::Start,
+            Tile::Start => Tile::Fruit,
+            Tile::Fruit => Tile::Empty,
+            Tile::Empty => Tile::Wall,
+        }
+    }
+}
+
+/// Reads a board drawn as text, one line per row from the top: `#` is a wall, `.` is empty,
+/// `S` is where the snake starts and `F` is somewhere fruit appears.
+///
+/// Every row has to be as wide as the first. Exactly one `S` is needed, away from the edge of
+/// the board (which is walled over unless wrap-around is on), with an empty cell to its west
+/// for the tail.
+pub fn parse_level(text: &str) -> Result<CustomLevel, String> {
+    let rows: Vec<&str> = text.trim_end().lines().map(str::trim_end).collect();
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    if width == 0 {
+        return Err("the level is empty".to_string());
+    }
+    let height = rows.len() as i32;
+
+    let mut walls = Vec::new();
+    let mut starts = Vec::new();
+    let mut fruit_spawns = Vec::new();
+    for (row, line) in rows.iter().enumerate() {
+        if line.chars().count() != width {
+            return Err(format!(
+                "line {} is {} wide, but the first is {}",
+                row + 1,
+                line.chars().count(),
+                width
+            ));
+        }
+        // The first line is the top of the board, and y points up.
+        let y = height - 1 - row as i32;
+        for (x, tile) in line.chars().enumerate() {
+            let cell = Position((x as i32, y));
+            match Tile::from_char(tile) {
+                Some(Tile::Wall) => walls.push(cell),
+                Some(Tile::Empty) => {}
+                Some(Tile::Start) => starts.push((row + 1, x + 1, cell)),
+                Some(Tile::Fruit) => fruit_spawns.push(cell),
+                None => {
+                    return Err(format!(
+                        "line {}, column {}: {:?} isn't a tile, expected one of '#', '.', 'S' \
+                         or 'F'",
+                        row + 1,
+                        x + 1,
+                        tile
+                    ))
+                }
+            }
+        }
+    }
+
+    let start = match starts[..] {
+        [(_, _, start)] => start,
+        [] => return Err("there is no start, marked 'S'".to_string()),
+        [(first_row, first_column, _), (row, column, _), ..] => {
+            return Err(format!(
+                "there is more than one start: line {}, column {} and line {}, column {}",
+                first_row, first_column, row, column
+            ))
+        }
+    };
+    let Position((x, y)) = start;
+    let tail = Position((x - 1, y));
+    let on_edge =
+        |Position((x, y)): Position| x == 0 || y == 0 || x == width as i32 - 1 || y == height - 1;
+    if on_edge(start) {
+        return Err("the start can't be on the edge of the board".to_string());
+    }
+    if walls.contains(&tail) {
+        return Err("the start needs an empty cell to its west, for the tail".to_string());
+    }
+    let has_room = (0..width as i32)
+        .flat_map(|x| (0..height).map(move |y| Position((x, y))))
+        .any(|cell| !on_edge(cell) && cell != start && cell != tail && !walls.contains(&cell));
+    if !has_room {
+        return Err("there is nowhere left for fruit".to_string());
+    }
+
+    Ok(CustomLevel {
+        width: width as u32,
+        height: height as u32,
+        walls,
+        start,
+        fruit_spawns,
+    })
+}
+
+/// `config`, playing on `level`. The campaign and two players don't apply to a drawn board.
+pub fn with_level(config: &Config, level: CustomLevel) -> Config {
+    Config {
+        board_width: level.width,
+        board_height: level.height,
+        two_player: false,
+        campaign: false,
+        custom_level: Some(level),
+        ..config.clone()
+    }
+}
+
+/// How long "LEVEL N" is shown for at the start of each level, with everything held still.
+pub const LEVEL_BANNER_SECONDS: f32 = 1.5;
+
+/// Tunable settings for a game. Anything missing when it's read from a settings file keeps
+/// its default.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub board_width: u32,
+    pub board_height: u32,
+    /// Seconds between each movement of the snake.
+    pub tick_seconds: f32,
+    pub wrap_mode: WrapMode,
+    /// Seed for fruit placement. A fresh seed is picked for every game when this is `None`.
+    pub seed: Option<u64>,
+    pub layout: Layout,
+    /// How many walls `Layout::Random` scatters.
+    pub random_wall_count: u32,
+    /// How the autopilot steers.
+    pub ai_strategy: ai::AiStrategy,
+    /// How many times the snake can crash in a game. The last crash ends it.
+    pub lives: u32,
+    /// How many times longer each move takes during `PowerUp::SlowMo`.
+    pub slow_mo_factor: f32,
+    /// How many moves `PowerUp::Rewind` goes back, up to `MAX_REWIND_TICKS`.
+    pub rewind_ticks: usize,
+    /// Adds a second snake, for another player sharing the keyboard.
+    pub two_player: bool,
+    /// What each kind of fruit is worth, and how often each turns up.
+    pub fruit_values: FruitValues,
+    /// With two players, running into the other snake only crashes the shorter one: a longer
+    /// snake eats it, grows by its length and scores a fruit for each part. Two the same length
+    /// both crash.
+    pub devouring: bool,
+    /// Survival: a ring of walls closes in every `shrink_seconds`, until there's hardly any
+    /// board left.
+    pub shrinking: bool,
+    pub shrink_seconds: f32,
+    /// Puts a pair of linked portals on the board.
+    pub portals: bool,
+    /// How many `MovingWall`s patrol the board.
+    pub moving_walls: u32,
+    /// Plays through `LEVELS` in order, instead of one endless board.
+    pub campaign: bool,
+    /// Time attack: the game ends when the clock runs out, starting from
+    /// `time_attack_seconds`. Every fruit buys `TIME_BONUS_SECONDS` more.
+    pub time_attack: bool,
+    pub time_attack_seconds: f32,
+    /// Rush: an open board that always wraps around, with the snake speeding up the longer
+    /// the game lasts rather than for each fruit. The score is the seconds survived.
+    pub rush: bool,
+    /// Practice: nothing is fatal. A snake heading into a wall waits in front of it until
+    /// turned, and one crossing its own body passes over it. None of it counts as a score.
+    pub practice: bool,
+    /// Hardcore: no assists at all. There's one life, no practice, and no shields or slow
+    /// motion to pick up, and the score is kept apart from the usual high score.
+    pub hardcore: bool,
+    /// Fruit trail: every fruit eaten leaves its cell glowing for `TRAIL_SECONDS`, and coming
+    /// back to it in that time is worth `TRAIL_POINTS`.
+    pub fruit_trail: bool,
+    /// A fruit eaten within this many moves of the last one adds to the combo, which
+    /// multiplies its points.
+    pub combo_ticks: u32,
+    /// The language everything on screen is worded in.
+    pub lang: Lang,
+    /// A board to play on in place of `layout`, set up by `with_level`.
+    pub custom_level: Option<CustomLevel>,
+    /// How many segments the snake starts with, laid out in a line behind its head. Cut short
+    /// if that wouldn't fit on the board.
+    pub start_length: u32,
+    /// Wins the game once the snake is this long, instead of only when it fills the board.
+    pub target_length: Option<u32>,
+    pub difficulty: Difficulty,
+    /// Races the best game played on a board like this one, replayed from the same seed.
+    /// It's only drawn, and takes no part in the game.
+    pub ghost: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            board_width: 11,
+            board_height: 11,
+            tick_seconds: 0.2,
+            wrap_mode: WrapMode::Walls,
+            seed: None,
+            layout: Layout::Empty,
+            random_wall_count: 8,
+            ai_strategy: ai::AiStrategy::Bfs,
+            lives: 3,
+            slow_mo_factor: 2.0,
+            rewind_ticks: 5,
+            two_player: false,
+            devouring: false,
+            fruit_values: FruitValues::default(),
+            shrinking: false,
+            shrink_seconds: 10.0,
+            portals: false,
+            moving_walls: 0,
+            campaign: false,
+            time_attack: false,
+            time_attack_seconds: 60.0,
+            rush: false,
+            practice: false,
+            hardcore: false,
+            fruit_trail: false,
+            combo_ticks: 10,
+            lang: Lang::English,
+            custom_level: None,
+            start_length: 2,
+            target_length: None,
+            difficulty: Difficulty::Normal,
+            ghost: false,
+        }
+    }
+}
+
+impl Config {
+    /// How many lives the snake starts with, which in hardcore is just the one.
+    pub fn lives(&self) -> u32 {
+        if self.hardcore {
+            1
+        } else {
+            self.lives
+        }
+    }
+
+    /// Whether this is practice, which hardcore never is.
+    pub fn practice(&self) -> bool {
+        self.practice && !self.hardcore
+    }
+
+    /// How the edges of the board behave, which rush always wraps around.
+    pub fn wrap_mode(&self) -> WrapMode {
+        if self.rush {
+            WrapMode::Wrap
+        } else {
+            self.wrap_mode
+        }
+    }
+}
+
+/// Where the end of the snake's tail starts, with the rest of it heading east from there.
+const START_TAIL: Position = Position((1, 1));
+/// After losing a life the snake keeps its length, up to this.
+const RESPAWN_MAX_LENGTH: usize = 5;
+/// How long the snake flashes before moving again after losing a life.
+const RESPAWN_SECONDS: f32 = 1.0;
+
+/// Turns beyond this many waiting to be taken are dropped, so mashing keys can't leave the
+/// snake acting on stale presses long after they were made.
+const MAX_PENDING_TURNS: usize = 2;
+
+/// How hard, in cells, and for how long the screen shakes on crashing and on eating a golden
+/// fruit.
+const CRASH_SHAKE_MAGNITUDE: f32 = 0.4;
+const CRASH_SHAKE_SECONDS: f32 = 0.4;
+const GOLDEN_SHAKE_MAGNITUDE: f32 = 0.15;
+const GOLDEN_SHAKE_SECONDS: f32 = 0.2;
+
+/// Each fruit eaten multiplies the tick interval by this much, speeding the snake up, unless
+/// the difficulty says otherwise.
+const SPEED_RAMP: f32 = 0.95;
+/// The speed ramp never makes the tick interval shorter than this.
+const MIN_TICK_SECONDS: f32 = 0.06;
+
+/// In rush, the tick interval halves every this many seconds, down to `MIN_RUSH_TICK_SECONDS`.
+const RUSH_HALVING_SECONDS: f32 = 45.0;
+const MIN_RUSH_TICK_SECONDS: f32 = 0.03;
+
+/// How much each press of `+` or `-` changes the tick interval by, and the range they can take
+/// it over.
+const TICK_NUDGE: f32 = 0.01;
+const MIN_NUDGED_TICK: f32 = 0.05;
+const MAX_NUDGED_TICK: f32 = 0.5;
+
+/// Points for eating a fruit, before any bonus for eating it quickly.
+const FRUIT_POINTS: u32 = 10;
+/// The bonus for eating a fruit the instant it appears. It drops by a point every second.
+const MAX_SPEED_BONUS: f32 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FruitKind {
+    /// Grows the snake, by one unless `Config::fruit_values` says otherwise.
+    Normal,
+    /// Shrinks the snake, killing it if there isn't enough snake left.
+    Poison,
+}
+
+/// How long a golden fruit stays on the board.
+pub const GOLDEN_FRUIT_SECONDS: f32 = 5.0;
+
+/// What eating one kind of fruit does, and how often it turns up.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FruitValue {
+    /// Added to the score. A plain fruit's are also multiplied by the combo, after a bonus
+    /// for eating it quickly.
+    pub points: u32,
+    /// Segments added to the tail, or taken off it when negative. Losing every segment
+    /// behind the head is a crash.
+    pub growth: i32,
+    /// How likely it is to be picked, against the others' weights, each time a plain fruit
+    /// is eaten. Picking a plain one puts out nothing more than the next plain fruit, which
+    /// always comes back.
+    pub weight: u32,
+}
+
+/// What each kind of fruit does, tunable in the settings as e.g. `[fruit_values.poison]`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FruitValues {
+    pub normal: FruitValue,
+    pub poison: FruitValue,
+    /// Only one is out at a time, for `GOLDEN_FRUIT_SECONDS`.
+    pub golden: FruitValue,
+}
+
+impl Default for FruitValues {
+    fn default() -> FruitValues {
+        // Poison after a third of the fruit eaten, and golden fruit after a fifth.
+        FruitValues {
+            normal: FruitValue {
+                points: FRUIT_POINTS,
+                growth: 1,
+                weight: 7,
+            },
+            poison: FruitValue {
+                points: 0,
+                growth: -2,
+                weight: 5,
+            },
+            golden: FruitValue {
+                points: 50,
+                growth: 2,
+                weight: 3,
+            },
+        }
+    }
+}
+
+impl FruitValues {
+    fn weights(&self) -> [u32; 3] {
+        [self.normal.weight, self.poison.weight, self.golden.weight]
+    }
+}
+
+/// How long a cell glows after its fruit is eaten, in `Config::fruit_trail`.
+pub const TRAIL_SECONDS: f32 = 2.0;
+const TRAIL_POINTS: u32 = 3;
+
+/// A message shown for a moment, such as what a power-up does or an achievement earned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toast {
+    pub text: String,
+    /// How much longer it's shown for.
+    pub seconds: f32,
+}
+
+pub const TOAST_SECONDS: f32 = 3.0;
+const MAX_TOASTS: usize = 4;
+
+/// A rare pick-up that does something for a while, rather than feeding the snake.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerUp {
+    /// Lets the snake pass through walls and itself for `SHIELD_SECONDS`.
+    Shield,
+    /// Slows the snake down by `Config::slow_mo_factor` for `SLOW_MO_SECONDS`.
+    SlowMo,
+    /// Speeds the snake up by `BOOST_FACTOR` for `BOOST_SECONDS`.
+    Boost,
+    /// Undoes the next crash, by winding the snake back `Config::rewind_ticks` moves.
+    Rewind,
+}
+
+/// One in this many power-ups is a rewind, which only ever turns up with one player.
+const REWIND_ODDS: usize = 6;
+/// The most moves a rewind can go back, however many `Config::rewind_ticks` asks for, so that
+/// the history kept for it stays small.
+pub const MAX_REWIND_TICKS: usize = 50;
+
+/// Where the snakes were, and which way they were going, before one of their moves.
+pub struct Snapshot {
+    pub parts: Vec<Vec<Position>>,
+    pub directions: Vec<Direction>,
+}
+
+/// One in this many fruit eaten puts a power-up on the board, if there isn't one already.
+const POWER_UP_ODDS: usize = 8;
+/// How long a power-up waits on the board to be picked up.
+pub const POWER_UP_SECONDS: f32 = 6.0;
+pub const SHIELD_SECONDS: f32 = 5.0;
+pub const SLOW_MO_SECONDS: f32 = 5.0;
+pub const BOOST_SECONDS: f32 = 3.0;
+/// What a speed boost multiplies the time between moves by.
+const BOOST_FACTOR: f32 = 0.5;
+
+/// How much time each fruit adds to the clock in time attack.
+pub const TIME_BONUS_SECONDS: f32 = 2.0;
+
+/// Points for every ring of walls survived in a shrinking game.
+const SHRINK_POINTS: u32 = 25;
+/// The board stops shrinking before the space left inside is narrower than this.
+const SHRINK_MIN_SIDE: i32 = 3;
+
+/// What a particle is the color of.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleColor {
+    Fruit,
+    Poison,
+    Golden,
+    PowerUp(PowerUp),
+}
+
+/// A speck thrown out when something is eaten. Positions are in (fractional) cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub position: (f32, f32),
+    /// Cells per second.
+    pub velocity: (f32, f32),
+    pub color: ParticleColor,
+    /// Seconds left before it disappears.
+    pub lifetime: f32,
+}
+
+/// How many particles each thing eaten throws out.
+const PARTICLES_PER_BURST: usize = 12;
+/// No more particles than this are kept, however much is eaten at once.
+const MAX_PARTICLES: usize = 64;
+pub const PARTICLE_SECONDS: f32 = 0.5;
+/// How fast particles fly out, in cells per second.
+const PARTICLE_SPEED: f32 = 3.0;
+
+/// A snake on the board. There is one for each player.
+pub struct Snake {
+    /// Head first.
+    pub parts: Vec<Position>,
+    /// Where each part was before the last movement, so drawing can ease between the two.
+    pub previous_parts: Vec<Position>,
+    /// The current direction we are headed.
+    pub direction: Direction,
+    /// Turns the player has asked for, applied one per movement.
+    pub pending_turns: VecDeque<Direction>,
+    /// "length of N", only re-formatted when the snake grows so `render` doesn't allocate
+    /// every frame.
+    pub length_label: String,
+    /// Set once this snake has crashed for good. With two players, the other carries on.
+    pub dead: bool,
+}
+
+impl Snake {
+    fn new(parts: Vec<Position>, direction: Direction, lang: Lang) -> Snake {
+        Snake {
+            length_label: length_label(lang, parts.len()),
+            previous_parts: parts.clone(),
+            parts,
+            direction,
+            pending_turns: VecDeque::new(),
+            dead: false,
+        }
+    }
+}
+
+pub struct PlayState {
+    /// What's happened since the last `update`, which hands them back.
+    pub events: Vec<GameEvent>,
+    pub walls: Vec<Position>,
+    /// The first snake is player one's, and the only one the autopilot can steer.
+    pub snakes: Vec<Snake>,
+
+    pub fruit: Vec<(Position, FruitKind)>,
+    /// When the fruit last moved somewhere new, by `elapsed`. `None` while it's still where
+    /// the board started it.
+    pub fruit_spawn_time: Option<f32>,
+    /// How many free cells the last fruit was picked from. It's only ever placed on a free
+    /// cell, never retried, so this is 0 only when there was nowhere left to put it.
+    pub fruit_choices: usize,
+    /// A bonus fruit and the seconds it has left before it disappears.
+    pub golden_fruit: Option<(Position, f32)>,
+    /// Cells still glowing from fruit eaten there, and the seconds each has left. They're
+    /// only ever scored, never run into.
+    pub trail: Vec<(Position, f32)>,
+    /// A power-up waiting to be picked up, and the seconds it has left before it disappears.
+    pub power_up: Option<(Position, PowerUp, f32)>,
+    /// Two linked cells. A head moving into either comes out of the other, still heading the
+    /// same way.
+    pub portals: Option<(Position, Position)>,
+    pub moving_walls: Vec<MovingWall>,
+    /// Seconds of `PowerUp::Shield` left. Crashes are ignored while this is above zero.
+    pub shield_timer: f32,
+    /// Seconds of `PowerUp::SlowMo` left.
+    pub slow_mo_timer: f32,
+    /// Seconds of `PowerUp::Boost` left. Only one of this and `slow_mo_timer` runs at once.
+    pub boost_timer: f32,
+    /// Seconds the screen has left to shake for.
+    pub shake_timer: f32,
+    /// How far the screen shakes, in cells. This winds down to nothing with `shake_timer`.
+    pub shake_magnitude: f32,
+    pub particles: Vec<Particle>,
+    /// Messages to show for a moment, oldest first, added with `push_toast`.
+    pub toasts: VecDeque<Toast>,
+    /// Drives fruit placement, so two games with the same seed and inputs play out identically.
+    pub rng: Rng,
+    pub seed_label: String,
+
+    pub time_since_last_move: f32,
+    /// Seconds between movements. Starts at `Config::tick_seconds` and shrinks as fruit is eaten.
+    pub tick_interval: f32,
+    /// Seconds the player has added to `tick_interval` with `nudge_speed`, which stay added as
+    /// the snake speeds up.
+    pub tick_nudge: f32,
+    pub fruit_eaten: u32,
+    /// Seconds spent playing, not counting time paused.
+    pub elapsed: f32,
+    /// How many times the snake has changed direction.
+    pub turns: u32,
+    /// Filled in with `stats_label` once the game is over.
+    pub stats_label: String,
+    pub score: u32,
+    pub score_label: String,
+    /// What the points for the next fruit are multiplied by, if it's eaten in time.
+    pub combo: u32,
+    /// "x3", for when the combo is above one.
+    pub combo_label: String,
+    /// Moves since the last fruit was eaten.
+    pub ticks_since_fruit: u32,
+    pub time_since_last_fruit: f32,
+    pub speed_label: String,
+    /// Whether the snake is steering itself, with the player's turns ignored.
+    pub autopilot: bool,
+    /// Set if the autopilot was ever switched on, which keeps the game off the high score.
+    pub autopilot_used: bool,
+    /// The route `AiStrategy::Hamiltonian` follows, worked out once at the start of the game.
+    pub hamiltonian_cycle: ai::HamiltonianCycle,
+    /// Crashes left before the game is over, counting this one.
+    pub lives: u32,
+    pub lives_label: String,
+    /// Crashes that `PowerUp::Rewind` will undo.
+    pub rewinds: u32,
+    pub rewinds_label: String,
+    /// The snakes before each of their last few moves, oldest first, to rewind to. Only kept
+    /// while there's a rewind to spend.
+    pub history: VecDeque<Snapshot>,
+    /// While this counts down after losing a life, the snake flashes and waits to go again.
+    pub respawn_timer: f32,
+    /// Seconds until the next ring of walls closes in, when `Config::shrinking`.
+    pub shrink_interval: f32,
+    /// How many rings have closed in so far.
+    pub rings: u32,
+    /// Which of `LEVELS` is being played, counting from 1, when `Config::campaign`.
+    pub level: u32,
+    /// "level N of M", cached like `Snake::length_label`.
+    pub level_label: String,
+    /// Fruit still to eat before the next level.
+    pub fruit_quota: u32,
+    /// "N fruit to go".
+    pub quota_label: String,
+    /// While this counts down at the start of a level, nothing moves and the level is shown.
+    pub level_timer: f32,
+    /// Seconds left on the clock, when `Config::time_attack`.
+    pub time_remaining: f32,
+    /// The clock, as "M:SS". Only re-formatted when the seconds change.
+    pub time_label: String,
+    /// Set once the clock runs out.
+    pub out_of_time: bool,
+    /// What ended the game, set once it is lost: when the last life is gone, or when every
+    /// snake has crashed.
+    pub death_cause: Option<DeathCause>,
+    /// Set by `fast_drop`, so the next update moves without waiting for the tick.
+    pub fast_drop: bool,
+    /// What the game was started with, so that it can be replayed.
+    pub seed: u64,
+    /// Every frame played so far, by `play_frame`.
+    pub recording: Vec<Frame>,
+    /// Who won a two player game, filled in once it's over.
+    pub winner_label: String,
+    /// Seconds since the game ended, for the death animation and the game over screen.
+    /// Nothing in `update` advances it, since the game is over by then.
+    pub death_timer: f32,
+    /// Set once the snake fills every free cell, leaving nowhere for fruit to go.
+    pub won: bool,
+}
+
+impl PlayState {
+    /// Whether the game has ended, one way or another.
+    pub fn is_over(&self) -> bool {
+        self.is_dead() || self.won || self.out_of_time
+    }
+
+    /// Whether the game was lost, rather than won or timed out.
+    pub fn is_dead(&self) -> bool {
+        self.death_cause.is_some()
+    }
+
+    /// Whether any snake has grown to `Config::target_length`, which wins the game.
+    pub fn reached_target(&self, config: &Config) -> bool {
+        config.target_length.is_some_and(|target| {
+            self.snakes
+                .iter()
+                .any(|snake| snake.parts.len() >= target as usize)
+        })
+    }
+
+    /// Every cell on the board that nothing (walls, snake or fruit) currently occupies.
+    pub fn free_cells(&self, config: &Config) -> Vec<Position> {
+        let mut free_cells = Vec::new();
+        for x in 0..config.board_width as i32 {
+            for y in 0..config.board_height as i32 {
+                let cell = Position((x, y));
+                let occupied = self.walls.contains(&cell)
+                    || self.snakes.iter().any(|snake| snake.parts.contains(&cell))
+                    || self.fruit.iter().any(|(fruit, _)| *fruit == cell)
+                    || matches!(self.golden_fruit, Some((golden, _)) if golden == cell)
+                    || matches!(self.power_up, Some((power_up, _, _)) if power_up == cell)
+                    || matches!(self.portals, Some((a, b)) if a == cell || b == cell)
+                    || self.moving_walls.iter().any(|wall| wall.position == cell);
+                if !occupied {
+                    free_cells.push(cell);
+                }
+            }
+        }
+        free_cells
+    }
+}
+
+/// Picks a random free cell, or `None` if the board is full.
+pub fn random_position_on_board(state: &mut PlayState, config: &Config) -> Option<Position> {
+    let free_cells = state.free_cells(config);
+    if free_cells.is_empty() {
+        return None;
+    }
+    Some(free_cells[state.rng.gen_range(0, free_cells.len())])
+}
+
+/// Picks where the next fruit goes: one of the custom level's fruit spawns while any are
+/// free, otherwise any free cell.
+fn fruit_position(state: &mut PlayState, config: &Config) -> Option<Position> {
+    let spawns = config
+        .custom_level
+        .as_ref()
+        .map_or(&[][..], |level| &level.fruit_spawns[..]);
+    let free_cells = state.free_cells(config);
+    let free_spawns: Vec<Position> = free_cells
+        .iter()
+        .filter(|cell| spawns.contains(cell))
+        .cloned()
+        .collect();
+    let choices = if free_spawns.is_empty() {
+        free_cells
+    } else {
+        free_spawns
+    };
+    state.fruit_choices = choices.len();
+    if choices.is_empty() {
+        return None;
+    }
+    Some(choices[state.rng.gen_range(0, choices.len())])
+}
+
+pub fn reset_state(config: &Config, seed: u64) -> PlayState {
+    let level_config;
+    let config = if config.campaign {
+        level_config = campaign_config(config, 1);
+        &level_config
+    } else {
+        config
+    };
+
+    let mut rng = Rng::new(seed);
+    let walls = build_walls(config, &mut rng);
+    let tick_interval = config.difficulty.starting_tick(config.tick_seconds);
+
+    let mut state = PlayState {
+        rng,
+        seed_label: config.lang.fill(Text::Seed, &[&seed]),
+        events: Vec::new(),
+        walls,
+        snakes: starting_snakes(config),
+        time_since_last_move: 0.0,
+        tick_interval,
+        tick_nudge: 0.0,
+        fruit_eaten: 0,
+        elapsed: 0.0,
+        turns: 0,
+        stats_label: String::new(),
+        score: 0,
+        score_label: score_label(config.lang, 0),
+        combo: 1,
+        combo_label: combo_label(1),
+        ticks_since_fruit: 0,
+        time_since_last_fruit: 0.0,
+        speed_label: speed_label(config.lang, tick_interval),
+        fruit: Vec::new(),
+        fruit_spawn_time: None,
+        fruit_choices: 0,
+        trail: Vec::new(),
+        golden_fruit: None,
+        power_up: None,
+        portals: None,
+        moving_walls: Vec::new(),
+        shield_timer: 0.0,
+        slow_mo_timer: 0.0,
+        boost_timer: 0.0,
+        shake_timer: 0.0,
+        shake_magnitude: 0.0,
+        particles: Vec::new(),
+        toasts: VecDeque::new(),
+        autopilot: false,
+        autopilot_used: false,
+        hamiltonian_cycle: ai::HamiltonianCycle::new(config),
+        lives: config.lives(),
+        lives_label: lives_label(config.lang, config.lives()),
+        rewinds: 0,
+        rewinds_label: rewinds_label(config.lang, 0),
+        history: VecDeque::new(),
+        respawn_timer: 0.0,
+        shrink_interval: config.shrink_seconds,
+        rings: 0,
+        level: 1,
+        level_label: level_label(config.lang, 1),
+        fruit_quota: LEVELS[0].fruit_quota,
+        quota_label: quota_label(config.lang, LEVELS[0].fruit_quota),
+        level_timer: 0.0,
+        time_remaining: config.time_attack_seconds,
+        time_label: time_label(config.time_attack_seconds),
+        out_of_time: false,
+        death_cause: None,
+        fast_drop: false,
+        seed,
+        recording: Vec::new(),
+        winner_label: String::new(),
+        death_timer: 0.0,
+        won: false,
+    };
+    fill_board(&mut state, config);
+
+    state
+}
+
+/// `config`, with the board and speed of the campaign's `level` in place of its own.
+fn campaign_config(config: &Config, level: u32) -> Config {
+    let level = &LEVELS[level as usize - 1];
+    Config {
+        layout: level.layout,
+        random_wall_count: level.random_wall_count,
+        moving_walls: level.moving_walls,
+        tick_seconds: level.tick_seconds,
+        ..config.clone()
+    }
+}
+
+/// Where player one's snake starts, head first and heading east. Its head and the cell behind
+/// it are also where it comes back after losing a life.
+fn start(config: &Config) -> Vec<Position> {
+    match &config.custom_level {
+        // Back from the start for as long as there's room, which is always at least one cell.
+        Some(level) => {
+            let Position((x, y)) = level.start;
+            let mut parts = vec![level.start];
+            for behind in 1..config.start_length.max(2) as i32 {
+                let cell = Position((x - behind, y));
+                if !on_board(config, cell) || level.walls.contains(&cell) {
+                    break;
+                }
+                parts.push(cell);
+            }
+            parts
+        }
+        // Along the bottom row, leaving at least one cell between the head and the border.
+        None => {
+            let Position((tail_x, y)) = START_TAIL;
+            let longest = config.board_width as i32 - 3;
+            let length = (config.start_length as i32).min(longest).max(2);
+            (0..length)
+                .map(|behind| Position((tail_x + length - 1 - behind, y)))
+                .collect()
+        }
+    }
+}
+
+/// Snakes at their starting positions, one for each player.
+fn starting_snakes(config: &Config) -> Vec<Snake> {
+    let mut snakes = vec![Snake::new(start(config), Direction::East, config.lang)];
+    if config.two_player {
+        // Player two starts in the opposite corner, heading the other way.
+        snakes.push(Snake::new(
+            second_start(config),
+            Direction::West,
+            config.lang,
+        ));
+    }
+    snakes
+}
+
+/// The border, if there is one, and the obstacles of `config.layout` or the custom level.
+fn build_walls(config: &Config, rng: &mut Rng) -> Vec<Position> {
+    let mut walls = Vec::new();
+    if config.rush {
+        return walls;
+    }
+    let right = config.board_width as i32 - 1;
+    let top = config.board_height as i32 - 1;
+
+    // With wrap-around there is no border, so the only thing to bite is yourself.
+    if config.wrap_mode() == WrapMode::Walls {
+        for x in 0..=right {
+            walls.push(Position((x, 0)));
+            walls.push(Position((x, top)));
+        }
+        for y in 1..top {
+            walls.push(Position((0, y)));
+            walls.push(Position((right, y)));
+        }
+    }
+
+    match &config.custom_level {
+        Some(level) => {
+            for wall in level.walls.iter().cloned() {
+                if !walls.contains(&wall) {
+                    walls.push(wall);
+                }
+            }
+        }
+        None => add_obstacles(config, rng, &mut walls, start(config)[0]),
+    }
+    walls
+}
+
+/// Puts out the first fruit, and the portals and moving walls if there are any.
+fn fill_board(state: &mut PlayState, config: &Config) {
+    let fruit = fruit_position(state, config).expect("a fresh board has free cells");
+    state.fruit.push((fruit, FruitKind::Normal));
+    if config.portals {
+        place_portals(state, config);
+    }
+    if !config.rush {
+        for _ in 0..config.moving_walls {
+            add_moving_wall(state, config);
+        }
+    }
+}
+
+/// Moves on to the next of `LEVELS`, or wins the game after the last. The score and lives carry
+/// over; the board and the snakes start afresh.
+fn next_level(state: &mut PlayState, config: &Config) {
+    if state.level as usize == LEVELS.len() {
+        state.won = true;
+        return;
+    }
+
+    state.level += 1;
+    state.events.push(GameEvent::LevelUp);
+    let config = campaign_config(config, state.level);
+    state.walls = build_walls(&config, &mut state.rng);
+    state.snakes = starting_snakes(&config);
+    state.hamiltonian_cycle = ai::HamiltonianCycle::new(&config);
+    state.history.clear();
+    state.fruit.clear();
+    state.fruit_spawn_time = None;
+    state.trail.clear();
+    state.golden_fruit = None;
+    state.power_up = None;
+    state.portals = None;
+    state.moving_walls.clear();
+    fill_board(state, &config);
+
+    state.tick_interval = nudged_tick(state, config.difficulty.starting_tick(config.tick_seconds));
+    state.speed_label = speed_label(config.lang, state.tick_interval);
+    state.time_since_last_move = 0.0;
+    state.shrink_interval = config.shrink_seconds;
+    state.rings = 0;
+    state.level_label = level_label(config.lang, state.level);
+    state.fruit_quota = LEVELS[state.level as usize - 1].fruit_quota;
+    state.quota_label = quota_label(config.lang, state.fruit_quota);
+    state.level_timer = LEVEL_BANNER_SECONDS;
+}
+
+/// Sets a `MovingWall` off from a random free cell, away from where the snakes start.
+fn add_moving_wall(state: &mut PlayState, config: &Config) {
+    let cells: Vec<Position> = state
+        .free_cells(config)
+        .into_iter()
+        .filter(|cell| !in_start_area(config, *cell))
+        .collect();
+    if cells.is_empty() {
+        return;
+    }
+    let position = cells[state.rng.gen_range(0, cells.len())];
+    let direction = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ][state.rng.gen_range(0, 4)];
+    state.moving_walls.push(MovingWall {
+        position,
+        direction,
+    });
+}
+
+/// Steps every `MovingWall` along, turning back any that would hit something. One boxed in on
+/// both sides stays where it is.
+fn move_walls(state: &mut PlayState, config: &Config) {
+    for index in 0..state.moving_walls.len() {
+        let MovingWall {
+            position,
+            direction,
+        } = state.moving_walls[index];
+        let blocked = |cell: Position| {
+            !on_board(config, cell)
+                || state.walls.contains(&cell)
+                || state.moving_walls.iter().any(|wall| wall.position == cell)
+        };
+
+        let reverse = match direction {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        };
+        let step = [direction, reverse]
+            .into_iter()
+            .map(|direction| (direction, next_position(position, direction, config)))
+            .find(|(_, cell)| !blocked(*cell));
+        if let Some((direction, position)) = step {
+            state.moving_walls[index] = MovingWall {
+                position,
+                direction,
+            };
+        }
+    }
+}
+
+/// Puts both portals somewhere new, or takes them away if there isn't room for two.
+fn place_portals(state: &mut PlayState, config: &Config) {
+    state.portals = None;
+    let Some(a) = random_position_on_board(state, config) else {
+        return;
+    };
+    // Claim the first cell, so the second can't land on it too.
+    state.portals = Some((a, a));
+    state.portals = random_position_on_board(state, config).map(|b| (a, b));
+}
+
+/// Where player two's snake starts: the top right corner, mirroring player one.
+fn second_start(config: &Config) -> Vec<Position> {
+    start(config)
+        .into_iter()
+        .map(|Position((x, y))| {
+            Position((
+                config.board_width as i32 - 1 - x,
+                config.board_height as i32 - 1 - y,
+            ))
+        })
+        .collect()
+}
+
+/// Whether the board has room for two players: neither snake starts on the other, or where
+/// the other moves to first.
+pub fn two_players_fit(config: &Config) -> bool {
+    let mut first = start(config);
+    first.push(next_position(first[0], Direction::East, config));
+    let mut second = second_start(config);
+    second.push(next_position(second[0], Direction::West, config));
+    !first.iter().any(|cell| second.contains(cell))
+}
+
+/// Obstacles are never placed this close to where a snake starts, so it has room to get going.
+fn in_start_area(config: &Config, Position((x, y)): Position) -> bool {
+    // Anywhere on the rows either side of the body, from just behind it to a few cells ahead.
+    let near = |parts: Vec<Position>, ahead: i32| {
+        let Position((head_x, row)) = parts[0];
+        let Position((tail_x, _)) = parts[parts.len() - 1];
+        let (left, right) = if ahead > 0 {
+            (tail_x - 1, head_x + ahead)
+        } else {
+            (head_x + ahead, tail_x + 1)
+        };
+        (left..=right).contains(&x) && (row - 1..=row + 1).contains(&y)
+    };
+    near(start(config), 3) || (config.two_player && near(second_start(config), -3))
+}
+
+fn on_board(config: &Config, Position((x, y)): Position) -> bool {
+    x >= 0 && y >= 0 && x < config.board_width as i32 && y < config.board_height as i32
+}
+
+/// Whether every cell that isn't a wall can be reached from `start`.
+fn all_open_cells_reachable(config: &Config, walls: &[Position], start: Position) -> bool {
+    let open_cells = (config.board_width * config.board_height) as usize - walls.len();
+
+    let mut reached = vec![start];
+    let mut frontier = vec![start];
+    while let Some(cell) = frontier.pop() {
+        for direction in [
+            Direction::North,
+            Direction::South,
+            Direction::West,
+            Direction::East,
+        ] {
+            let neighbour = next_position(cell, direction, config);
+            if on_board(config, neighbour)
+                && !walls.contains(&neighbour)
+                && !reached.contains(&neighbour)
+            {
+                reached.push(neighbour);
+                frontier.push(neighbour);
+            }
+        }
+    }
+
+    reached.len() == open_cells
+}
+
+/// Adds the interior walls of `config.layout` to `walls`.
+fn add_obstacles(config: &Config, rng: &mut Rng, walls: &mut Vec<Position>, start: Position) {
+    let width = config.board_width as i32;
+    let height = config.board_height as i32;
+    let (center_x, center_y) = (width / 2, height / 2);
+
+    let mut obstacles = Vec::new();
+    match config.layout {
+        Layout::Empty => {}
+        Layout::Cross => {
+            let arm = (width.min(height) / 2 - 3).max(0);
+            for offset in -arm..=arm {
+                obstacles.push(Position((center_x + offset, center_y)));
+                obstacles.push(Position((center_x, center_y + offset)));
+            }
+        }
+        Layout::Pillars => {
+            for x in (3..width - 3).step_by(4) {
+                for y in (3..height - 3).step_by(4) {
+                    obstacles.push(Position((x, y)));
+                }
+            }
+        }
+        Layout::Random => {
+            let mut placed = 0;
+            // Give up eventually rather than spin forever on a board too cramped to fit them.
+            let count = config.difficulty.wall_count(config.random_wall_count);
+            for _ in 0..count * 20 {
+                if placed == count {
+                    break;
+                }
+
+                let cell = Position((
+                    rng.gen_range(0, width as usize) as i32,
+                    rng.gen_range(0, height as usize) as i32,
+                ));
+                if walls.contains(&cell) || in_start_area(config, cell) {
+                    continue;
+                }
+
+                walls.push(cell);
+                if all_open_cells_reachable(config, walls, start) {
+                    placed += 1;
+                } else {
+                    walls.pop();
+                }
+            }
+        }
+    }
+
+    for obstacle in obstacles {
+        if on_board(config, obstacle)
+            && !in_start_area(config, obstacle)
+            && !walls.contains(&obstacle)
+        {
+            walls.push(obstacle);
+        }
+    }
+}
+
+pub fn lives_label(lang: Lang, lives: u32) -> String {
+    lang.fill(Text::Lives, &[&lives])
+}
+
+pub fn rewinds_label(lang: Lang, rewinds: u32) -> String {
+    lang.fill(Text::Rewinds, &[&rewinds])
+}
+
+pub fn length_label(lang: Lang, length: usize) -> String {
+    lang.fill(Text::Length, &[&length])
+}
+
+pub fn score_label(lang: Lang, score: u32) -> String {
+    lang.fill(Text::Score, &[&score])
+}
+
+fn combo_label(combo: u32) -> String {
+    format!("x{}", combo)
+}
+
+/// Points for eating a fruit worth `points` `time_since_last_fruit` seconds after the previous
+/// one.
+pub fn fruit_points(points: u32, time_since_last_fruit: f32) -> u32 {
+    points + (MAX_SPEED_BONUS - time_since_last_fruit).max(0.0) as u32
+}
+
+/// A summary of the game so far, for the game over screen.
+pub fn stats_label(lang: Lang, state: &PlayState) -> String {
+    let seconds = state.elapsed as u32;
+    lang.fill(
+        Text::Stats,
+        &[
+            &state.fruit_eaten,
+            &(seconds / 60),
+            &format!("{:02}", seconds % 60),
+            &state.turns,
+        ],
+    )
+}
+
+fn time_label(seconds: f32) -> String {
+    let seconds = seconds.ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn level_label(lang: Lang, level: u32) -> String {
+    lang.fill(Text::Level, &[&level, &LEVELS.len()])
+}
+
+fn quota_label(lang: Lang, fruit_quota: u32) -> String {
+    lang.fill(Text::Quota, &[&fruit_quota])
+}
+
+pub fn speed_label(lang: Lang, tick_interval: f32) -> String {
+    lang.fill(Text::Speed, &[&format!("{:.1}", 1.0 / tick_interval)])
+}
+
+/// The tick interval after `fruit_eaten` fruit, for a game asking for `tick_seconds`.
+pub fn ramped_tick_interval(difficulty: Difficulty, tick_seconds: f32, fruit_eaten: u32) -> f32 {
+    let base_tick = difficulty.starting_tick(tick_seconds);
+    (base_tick * difficulty.speed_ramp().powi(fruit_eaten as i32)).max(MIN_TICK_SECONDS)
+}
+
+/// Grows the snake by one. The new segment starts stacked on the tail and is left behind there
+/// as the snake moves on, so it never lands on a cell the snake hasn't been through.
+pub fn extend_snake_body(snake: &mut Snake) {
+    if let Some(tail) = snake.parts.last().cloned() {
+        snake.parts.push(tail);
+    }
+}
+
+/// Adds `growth` segments to the snake at `index`, or takes them off when it's negative.
+fn change_length(state: &mut PlayState, config: &Config, index: usize, growth: i32) {
+    let snake = &mut state.snakes[index];
+    if growth < 0 {
+        let shrink = growth.unsigned_abs() as usize;
+        // The head always survives the shrink; a snake with nothing behind it is dead.
+        if snake.parts.len() <= shrink {
+            lose_life(state, config, index, DeathCause::Poison);
+            return;
+        }
+        let length = snake.parts.len() - shrink;
+        snake.parts.truncate(length);
+    } else {
+        // Never longer than there are cells to fit in, or it would have to bite itself.
+        let cells =
+            ((config.board_width * config.board_height) as usize).saturating_sub(state.walls.len());
+        for _ in 0..growth {
+            if snake.parts.len() < cells {
+                extend_snake_body(snake);
+            }
+        }
+    }
+    snake.length_label = length_label(config.lang, snake.parts.len());
+}
+
+/// Feeds the snake at `index`. Score and speed are shared between both players.
+fn eat_fruit(state: &mut PlayState, config: &Config, index: usize) {
+    if config.fruit_trail {
+        state
+            .trail
+            .push((state.snakes[index].parts[0], TRAIL_SECONDS));
+    }
+    change_length(state, config, index, config.fruit_values.normal.growth);
+    state.fruit_eaten += 1;
+    state.combo = if state.fruit_eaten > 1 && state.ticks_since_fruit <= config.combo_ticks {
+        state.combo + 1
+    } else {
+        1
+    };
+    state.combo_label = combo_label(state.combo);
+    state.ticks_since_fruit = 0;
+    // In rush, only time scores.
+    if !config.rush {
+        let points = fruit_points(
+            config.fruit_values.normal.points,
+            state.time_since_last_fruit,
+        );
+        state.score += points * state.combo;
+        state.score_label = score_label(config.lang, state.score);
+    }
+    state.time_since_last_fruit = 0.0;
+    if config.time_attack {
+        state.time_remaining += TIME_BONUS_SECONDS;
+        state.time_label = time_label(state.time_remaining);
+    }
+    if config.campaign {
+        state.fruit_quota -= 1;
+        state.quota_label = quota_label(config.lang, state.fruit_quota);
+        if state.fruit_quota == 0 {
+            next_level(state, config);
+            return;
+        }
+        // Each level sets its own pace, and speeds up from there.
+        let level = &LEVELS[state.level as usize - 1];
+        let eaten = level.fruit_quota - state.fruit_quota;
+        let tick = ramped_tick_interval(config.difficulty, level.tick_seconds, eaten);
+        state.tick_interval = nudged_tick(state, tick);
+    } else if !config.rush {
+        let tick = ramped_tick_interval(config.difficulty, config.tick_seconds, state.fruit_eaten);
+        state.tick_interval = nudged_tick(state, tick);
+    }
+    state.speed_label = speed_label(config.lang, state.tick_interval);
+
+    // Any poison moves along with the fruit, so it doesn't pile up on the board.
+    state.fruit.retain(|(_, kind)| *kind != FruitKind::Poison);
+    match fruit_position(state, config) {
+        Some(fruit) => state.fruit.push((fruit, FruitKind::Normal)),
+        None => state.won = true,
+    }
+    state.fruit_spawn_time = Some(state.elapsed);
+
+    // Whatever's picked goes out alongside the next plain fruit.
+    match state.rng.pick_weighted(&config.fruit_values.weights()) {
+        1 => {
+            if let Some(poison) = random_position_on_board(state, config) {
+                state.fruit.push((poison, FruitKind::Poison));
+            }
+        }
+        2 if state.golden_fruit.is_none() => {
+            state.golden_fruit = random_position_on_board(state, config)
+                .map(|golden| (golden, GOLDEN_FRUIT_SECONDS));
+        }
+        _ => {}
+    }
+
+    if state.power_up.is_none() && state.rng.gen_range(0, POWER_UP_ODDS) == 0 {
+        let power_ups: &[PowerUp] = if config.hardcore {
+            &[PowerUp::Boost]
+        } else {
+            &[PowerUp::Shield, PowerUp::SlowMo, PowerUp::Boost]
+        };
+        let rewind = !config.hardcore && !config.two_player;
+        let power_up = if rewind && state.rng.gen_range(0, REWIND_ODDS) == 0 {
+            PowerUp::Rewind
+        } else {
+            power_ups[state.rng.gen_range(0, power_ups.len())]
+        };
+        state.power_up =
+            random_position_on_board(state, config).map(|cell| (cell, power_up, POWER_UP_SECONDS));
+    }
+}
+
+fn eat_poison(state: &mut PlayState, config: &Config, index: usize) {
+    let poison = config.fruit_values.poison;
+    if !config.rush && poison.points > 0 {
+        state.score += poison.points;
+        state.score_label = score_label(config.lang, state.score);
+    }
+    change_length(state, config, index, poison.growth);
+}
+
+/// Called when the snake at `index` crashes.
+///
+/// With one player, this takes a life, and unless that was the last one puts the snake back at
+/// the start, no longer than `RESPAWN_MAX_LENGTH`. With two there are no lives: the snake is
+/// out, and stays where it is as one more thing for the other to avoid.
+fn lose_life(state: &mut PlayState, config: &Config, index: usize, cause: DeathCause) {
+    if state.rewinds > 0 && !state.history.is_empty() {
+        rewind(state, config);
+        state.events.push(GameEvent::Rewound);
+        return;
+    }
+    take_life(state, config, index, cause);
+    state.events.push(if state.snakes[index].dead {
+        GameEvent::Died(cause)
+    } else {
+        GameEvent::Crashed(cause)
+    });
+}
+
+fn take_life(state: &mut PlayState, config: &Config, index: usize, cause: DeathCause) {
+    shake(state, CRASH_SHAKE_MAGNITUDE, CRASH_SHAKE_SECONDS);
+    if config.shrinking && state.snakes.len() == 1 {
+        // The start may well be walled over by now, so there's nowhere to come back to.
+        state.snakes[index].dead = true;
+        state.death_cause = Some(cause);
+        return;
+    }
+    if state.snakes.len() > 1 {
+        state.snakes[index].dead = true;
+        if state.snakes.iter().all(|snake| snake.dead) {
+            state.death_cause = Some(cause);
+            state.winner_label = winner_label(config.lang, &state.snakes);
+        }
+        return;
+    }
+
+    state.lives -= 1;
+    state.lives_label = lives_label(config.lang, state.lives);
+    if state.lives == 0 {
+        state.snakes[index].dead = true;
+        state.death_cause = Some(cause);
+        return;
+    }
+
+    // Everything behind the tail starts stacked on it, and unfolds as the snake moves off.
+    let snake = &mut state.snakes[index];
+    let length = snake.parts.len().clamp(2, RESPAWN_MAX_LENGTH);
+    let start = start(config);
+    *snake = Snake::new(vec![start[0]], Direction::East, config.lang);
+    snake.parts.resize(length, start[1]);
+    // Rewinding would take it back to before it started over.
+    state.history.clear();
+    snake.previous_parts.clone_from(&snake.parts);
+    snake.length_label = length_label(config.lang, length);
+    state.time_since_last_move = 0.0;
+    state.respawn_timer = RESPAWN_SECONDS;
+
+    // Nothing should be left hidden underneath the snake.
+    for fruit in 0..state.fruit.len() {
+        if state.snakes[index].parts.contains(&state.fruit[fruit].0) {
+            if let Some(cell) = random_position_on_board(state, config) {
+                state.fruit[fruit].0 = cell;
+            }
+        }
+    }
+    let parts = &state.snakes[index].parts;
+    if matches!(state.golden_fruit, Some((golden, _)) if parts.contains(&golden)) {
+        state.golden_fruit = None;
+    }
+    if matches!(state.power_up, Some((power_up, _, _)) if parts.contains(&power_up)) {
+        state.power_up = None;
+    }
+}
+
+/// Spends a rewind, putting the snakes back where they were the oldest move still in the
+/// history. They wait a moment, as after losing a life, to give the player time to turn.
+fn rewind(state: &mut PlayState, config: &Config) {
+    let Some(snapshot) = state.history.pop_front() else {
+        return;
+    };
+    state.history.clear();
+    state.rewinds -= 1;
+    state.rewinds_label = rewinds_label(config.lang, state.rewinds);
+    for (snake, (parts, direction)) in state
+        .snakes
+        .iter_mut()
+        .zip(snapshot.parts.into_iter().zip(snapshot.directions))
+    {
+        snake.previous_parts.clone_from(&parts);
+        snake.parts = parts;
+        snake.direction = direction;
+        snake.pending_turns.clear();
+        snake.length_label = length_label(config.lang, snake.parts.len());
+    }
+    shake(state, CRASH_SHAKE_MAGNITUDE, CRASH_SHAKE_SECONDS);
+    state.time_since_last_move = 0.0;
+    state.respawn_timer = RESPAWN_SECONDS;
+}
+
+/// Closes the next ring of walls in, one cell inside the last. Any snake caught under it is
+/// crushed, and anything else under it is moved or lost.
+fn shrink_board(state: &mut PlayState, config: &Config) {
+    // Without a border, the first ring goes around the very edge of the board.
+    let ring = match config.wrap_mode() {
+        WrapMode::Walls => state.rings as i32 + 1,
+        WrapMode::Wrap => state.rings as i32,
+    };
+    let right = config.board_width as i32 - 1 - ring;
+    let top = config.board_height as i32 - 1 - ring;
+    if right - ring - 1 < SHRINK_MIN_SIDE || top - ring - 1 < SHRINK_MIN_SIDE {
+        return;
+    }
+
+    let mut ring_cells = Vec::new();
+    for x in ring..=right {
+        ring_cells.push(Position((x, ring)));
+        ring_cells.push(Position((x, top)));
+    }
+    for y in ring + 1..top {
+        ring_cells.push(Position((ring, y)));
+        ring_cells.push(Position((right, y)));
+    }
+    for cell in ring_cells {
+        if !state.walls.contains(&cell) {
+            state.walls.push(cell);
+        }
+    }
+    state.rings += 1;
+    shake(state, CRASH_SHAKE_MAGNITUDE / 2.0, CRASH_SHAKE_SECONDS);
+
+    for index in 0..state.snakes.len() {
+        let snake = &state.snakes[index];
+        if !snake.dead && snake.parts.iter().any(|part| state.walls.contains(part)) {
+            lose_life(state, config, index, DeathCause::Wall);
+        }
+    }
+    if state.is_dead() {
+        return;
+    }
+    state.score += SHRINK_POINTS;
+    state.score_label = score_label(config.lang, state.score);
+
+    // Poison just goes, but there always has to be a fruit to go for.
+    state
+        .fruit
+        .retain(|(fruit, kind)| *kind == FruitKind::Normal || !state.walls.contains(fruit));
+    for index in 0..state.fruit.len() {
+        if state.walls.contains(&state.fruit[index].0) {
+            match random_position_on_board(state, config) {
+                Some(cell) => state.fruit[index].0 = cell,
+                None => state.won = true,
+            }
+        }
+    }
+    if matches!(state.golden_fruit, Some((golden, _)) if state.walls.contains(&golden)) {
+        state.golden_fruit = None;
+    }
+    if matches!(state.power_up, Some((power_up, _, _)) if state.walls.contains(&power_up)) {
+        state.power_up = None;
+    }
+    let walls = &state.walls;
+    state
+        .moving_walls
+        .retain(|moving_wall| !walls.contains(&moving_wall.position));
+    if matches!(state.portals, Some((a, b)) if state.walls.contains(&a) || state.walls.contains(&b))
+    {
+        place_portals(state, config);
+    }
+}
+
+/// Who won a two player game: whoever ended up longest.
+pub fn winner_label(lang: Lang, snakes: &[Snake]) -> String {
+    let one = snakes[0].parts.len();
+    let two = snakes[1].parts.len();
+    match one.cmp(&two) {
+        std::cmp::Ordering::Greater => lang.fill(Text::PlayerOneWins, &[&one, &two]),
+        std::cmp::Ordering::Less => lang.fill(Text::PlayerTwoWins, &[&two, &one]),
+        std::cmp::Ordering::Equal => lang.fill(Text::Draw, &[&one]),
+    }
+}
+
+/// The direction a thumbstick at `x`, `y` is pushed in, along whichever axis it is pushed
+/// furthest. `y` is positive when pushed up. Pushes within `deadzone` of the center are ignored.
+pub fn stick_to_direction(x: f32, y: f32, deadzone: f32) -> Option<Direction> {
+    if x.abs().max(y.abs()) <= deadzone {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x > 0.0 {
+            Direction::East
+        } else {
+            Direction::West
+        })
+    } else {
+        Some(if y > 0.0 {
+            Direction::North
+        } else {
+            Direction::South
+        })
+    }
+}
+
+/// The size of the biggest square cells that fit the whole board in a `width` by `height`
+/// space. The board fills one side, and is letterboxed along the other when their shapes
+/// don't match, so that cells are never stretched.
+pub fn cell_size_to_fit(config: &Config, width: f32, height: f32) -> f32 {
+    (width / config.board_width as f32).min(height / config.board_height as f32)
+}
+
+/// Makes the snake take its next step on the next update, rather than waiting for the tick.
+/// The tick starts over from that step.
+pub fn fast_drop(state: &mut PlayState) {
+    state.fast_drop = true;
+}
+
+/// Speeds the snake up a little, or slows it down, as far as it will go.
+pub fn nudge_speed(state: &mut PlayState, config: &Config, faster: bool) {
+    let by = if faster { -TICK_NUDGE } else { TICK_NUDGE };
+    let nudged = (state.tick_interval + by).clamp(MIN_NUDGED_TICK, MAX_NUDGED_TICK);
+    state.tick_nudge += nudged - state.tick_interval;
+    state.tick_interval = nudged;
+    state.speed_label = speed_label(config.lang, state.tick_interval);
+}
+
+/// `tick` with the player's nudges added, kept within the range they can reach.
+fn nudged_tick(state: &PlayState, tick: f32) -> f32 {
+    if state.tick_nudge == 0.0 {
+        tick
+    } else {
+        (tick + state.tick_nudge).clamp(MIN_NUDGED_TICK, MAX_NUDGED_TICK)
+    }
+}
+
+pub fn toggle_autopilot(state: &mut PlayState) {
+    state.autopilot = !state.autopilot;
+    state.autopilot_used |= state.autopilot;
+}
+
+/// Queues a turn to be taken at a later movement.
+///
+/// Turns are validated against the last queued turn rather than the current direction,
+/// otherwise two quick presses within one tick could fold the snake back onto itself.
+pub fn queue_turn(snake: &mut Snake, turn: Direction) {
+    if snake.pending_turns.len() >= MAX_PENDING_TURNS {
+        return;
+    }
+    let last_direction = snake
+        .pending_turns
+        .back()
+        .cloned()
+        .unwrap_or(snake.direction);
+
+    if turn != last_direction && !is_opposite_of(turn, last_direction) {
+        snake.pending_turns.push_back(turn);
+    }
+}
+
+/// Starts the screen shaking, unless it's already shaking harder.
+fn shake(state: &mut PlayState, magnitude: f32, seconds: f32) {
+    if magnitude > state.shake_magnitude {
+        state.shake_magnitude = magnitude;
+        state.shake_timer = seconds;
+    }
+}
+
+/// Throws a ring of particles out from the middle of `cell`.
+fn burst(state: &mut PlayState, Position((x, y)): Position, color: ParticleColor) {
+    // The ring is turned a little differently each time, so bursts don't all look the same.
+    let turn = state.fruit_eaten as f32 * 0.7;
+    for index in 0..PARTICLES_PER_BURST {
+        if state.particles.len() >= MAX_PARTICLES {
+            break;
+        }
+        let angle = turn + index as f32 * std::f32::consts::TAU / PARTICLES_PER_BURST as f32;
+        // Alternating speeds give the ring some depth.
+        let speed = PARTICLE_SPEED * if index % 2 == 0 { 1.0 } else { 0.6 };
+        state.particles.push(Particle {
+            position: (x as f32 + 0.5, y as f32 + 0.5),
+            velocity: (angle.cos() * speed, angle.sin() * speed),
+            color,
+            lifetime: PARTICLE_SECONDS,
+        });
+    }
+}
+
+/// Winds down the effects that run on their own, like the screen shaking. `update` does this,
+/// but it needs doing after the game is over too, so they can finish.
+pub fn update_effects(state: &mut PlayState, dt: f32) {
+    if state.shake_timer > dt {
+        // Shrinks evenly, so that it reaches nothing just as the timer runs out.
+        state.shake_magnitude -= state.shake_magnitude * dt / state.shake_timer;
+        state.shake_timer -= dt;
+    } else {
+        state.shake_magnitude = 0.0;
+        state.shake_timer = 0.0;
+    }
+
+    for particle in state.particles.iter_mut() {
+        particle.position.0 += particle.velocity.0 * dt;
+        particle.position.1 += particle.velocity.1 * dt;
+        particle.lifetime -= dt;
+    }
+    state.particles.retain(|particle| particle.lifetime > 0.0);
+
+    for toast in state.toasts.iter_mut() {
+        toast.seconds -= dt;
+    }
+    state.toasts.retain(|toast| toast.seconds > 0.0);
+}
+
+/// Shows `text` for `TOAST_SECONDS`, under any toasts already showing, but never more than
+/// `MAX_TOASTS` at once.
+pub fn push_toast(state: &mut PlayState, text: String) {
+    if state.toasts.len() == MAX_TOASTS {
+        state.toasts.pop_front();
+    }
+    state.toasts.push_back(Toast {
+        text,
+        seconds: TOAST_SECONDS,
+    });
+}
+
+/// Speeds the snake up for how long the game has gone on, and scores every second of it.
+fn update_rush(state: &mut PlayState, config: &Config) {
+    let halvings = state.elapsed / RUSH_HALVING_SECONDS;
+    let tick = config.difficulty.starting_tick(config.tick_seconds) * 0.5f32.powf(halvings);
+    let tick = nudged_tick(state, tick.max(MIN_RUSH_TICK_SECONDS));
+    let before = speed_label(config.lang, state.tick_interval);
+    state.tick_interval = tick;
+    let after = speed_label(config.lang, state.tick_interval);
+    if after != before {
+        state.speed_label = after;
+    }
+
+    let seconds = state.elapsed as u32;
+    if seconds != state.score {
+        state.score = seconds;
+        state.score_label = score_label(config.lang, state.score);
+    }
+}
+
+/// Seconds between movements right now: the ramped `tick_interval`, stretched out while slow-mo
+/// is running and squeezed while a speed boost is.
+pub fn move_interval(state: &PlayState, config: &Config) -> f32 {
+    if state.slow_mo_timer > 0.0 {
+        state.tick_interval * config.slow_mo_factor
+    } else if state.boost_timer > 0.0 {
+        state.tick_interval * BOOST_FACTOR
+    } else {
+        state.tick_interval
+    }
+}
+
+/// Advances the game by `dt` seconds, moving as many times as fit in them. `turns` are the
+/// directions asked for since the last update, in the order they were asked for, and steer the
+/// first snake. Any other snake is steered by queuing turns with `queue_turn` beforehand.
+/// Returns what happened along the way.
+pub fn update(
+    state: &mut PlayState,
+    config: &Config,
+    turns: &[Direction],
+    dt: f32,
+) -> Vec<GameEvent> {
+    advance(state, config, turns, dt);
+    let events = std::mem::take(&mut state.events);
+    for event in events.iter() {
+        match event {
+            GameEvent::PowerUp(power_up) => {
+                push_toast(
+                    state,
+                    config.lang.text(Text::PickedUp(*power_up)).to_string(),
+                );
+            }
+            GameEvent::LevelUp => push_toast(state, state.level_label.clone()),
+            GameEvent::Rewound => {
+                push_toast(state, config.lang.text(Text::Rewound).to_string());
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+fn advance(state: &mut PlayState, config: &Config, turns: &[Direction], dt: f32) {
+    for turn in turns.iter().cloned() {
+        queue_turn(&mut state.snakes[0], turn);
+    }
+
+    // Only good for this update: one asked for while frozen is dropped, not saved for later.
+    let fast_drop = std::mem::take(&mut state.fast_drop);
+    state.elapsed += dt;
+    if config.rush {
+        update_rush(state, config);
+    }
+    update_effects(state, dt);
+    if config.time_attack {
+        // The clock runs in real time, whatever the snake is doing.
+        let before = state.time_remaining;
+        state.time_remaining = (state.time_remaining - dt).max(0.0);
+        if state.time_remaining.ceil() != before.ceil() {
+            state.time_label = time_label(state.time_remaining);
+        }
+        if state.time_remaining <= 0.0 {
+            state.out_of_time = true;
+            state.stats_label = stats_label(config.lang, state);
+            return;
+        }
+    }
+    if state.respawn_timer > 0.0 {
+        // Like the countdown, turns still count while frozen.
+        state.respawn_timer -= dt;
+        return;
+    }
+    if state.level_timer > 0.0 {
+        state.level_timer -= dt;
+        return;
+    }
+    state.time_since_last_move += dt;
+    state.time_since_last_fruit += dt;
+    if let Some((golden, lifetime)) = state.golden_fruit {
+        let lifetime = lifetime - dt;
+        state.golden_fruit = if lifetime > 0.0 {
+            Some((golden, lifetime))
+        } else {
+            None
+        };
+    }
+    if let Some((cell, power_up, lifetime)) = state.power_up {
+        let lifetime = lifetime - dt;
+        state.power_up = if lifetime > 0.0 {
+            Some((cell, power_up, lifetime))
+        } else {
+            None
+        };
+    }
+    state.shield_timer = (state.shield_timer - dt).max(0.0);
+    state.slow_mo_timer = (state.slow_mo_timer - dt).max(0.0);
+    state.boost_timer = (state.boost_timer - dt).max(0.0);
+    state.trail.retain_mut(|(_, seconds)| {
+        *seconds -= dt;
+        *seconds > 0.0
+    });
+    if config.shrinking {
+        state.shrink_interval -= dt;
+        if state.shrink_interval <= 0.0 {
+            state.shrink_interval += config.shrink_seconds;
+            shrink_board(state, config);
+            if state.is_over() {
+                state.stats_label = stats_label(config.lang, state);
+                return;
+            }
+        }
+    }
+    // Moves happen in whole intervals, however the time arrives: a long frame can move the
+    // snake more than once, and what's left over counts toward the next move instead of being
+    // dropped, so the pace is the same at any frame rate.
+    if fast_drop {
+        state.time_since_last_move = 0.0;
+        move_snakes(state, config);
+    }
+    loop {
+        let interval = move_interval(state, config);
+        let frozen = state.respawn_timer > 0.0 || state.level_timer > 0.0;
+        if state.is_over() || frozen || state.time_since_last_move < interval {
+            break;
+        }
+        state.time_since_last_move -= interval;
+        move_snakes(state, config);
+    }
+}
+
+/// Moves every snake one cell, and sees what they ran into.
+fn move_snakes(state: &mut PlayState, config: &Config) {
+    state.ticks_since_fruit += 1;
+    if state.ticks_since_fruit > config.combo_ticks && state.combo > 1 {
+        state.combo = 1;
+        state.combo_label = combo_label(1);
+    }
+    if state.rewinds > 0 {
+        state.history.push_back(Snapshot {
+            parts: state
+                .snakes
+                .iter()
+                .map(|snake| snake.parts.clone())
+                .collect(),
+            directions: state.snakes.iter().map(|snake| snake.direction).collect(),
+        });
+        if state.history.len() > config.rewind_ticks.clamp(1, MAX_REWIND_TICKS) {
+            state.history.pop_front();
+        }
+    }
+    let direction = state.snakes[0].direction;
+    if state.autopilot {
+        let direction = match config.ai_strategy {
+            ai::AiStrategy::Bfs => ai::bfs_direction(state, config),
+            ai::AiStrategy::Hamiltonian => {
+                if !state.hamiltonian_cycle.fits(config) {
+                    state.hamiltonian_cycle = ai::HamiltonianCycle::new(config);
+                }
+                ai::hamiltonian_direction(state, config, &state.hamiltonian_cycle)
+            }
+        };
+        state.snakes[0].pending_turns.clear();
+        state.snakes[0].direction = direction;
+    }
+    for snake in state.snakes.iter_mut() {
+        if let Some(turn) = snake.pending_turns.pop_front() {
+            snake.direction = turn;
+        }
+    }
+    if state.snakes[0].direction != direction {
+        state.turns += 1;
+    }
+
+    let mut teleported = false;
+    for snake in state.snakes.iter_mut().filter(|snake| !snake.dead) {
+        snake.previous_parts.clone_from(&snake.parts);
+        let mut next_position = next_position(snake.parts[0], snake.direction, config);
+        if state.shield_timer > 0.0 {
+            // Passing through the border would leave the board, so come back in on the far side.
+            let Position((x, y)) = next_position;
+            next_position = Position((
+                x.rem_euclid(config.board_width as i32),
+                y.rem_euclid(config.board_height as i32),
+            ));
+        }
+        if let Some(exit) = portal_exit(state.portals, snake, next_position) {
+            next_position = exit;
+            teleported = true;
+        }
+        let blocked = state.walls.contains(&next_position)
+            || state
+                .moving_walls
+                .iter()
+                .any(|wall| wall.position == next_position);
+        if config.practice() && blocked && state.shield_timer <= 0.0 {
+            continue;
+        }
+        for part in snake.parts.iter_mut() {
+            std::mem::swap(part, &mut next_position);
+        }
+    }
+    // Portals move on once used, so they stay somewhere worth heading for.
+    if teleported {
+        place_portals(state, config);
+    }
+    let moving_walls_before = state.moving_walls.clone();
+    move_walls(state, config);
+
+    // Collision check. Every snake has moved, so two heads meeting crash into each other.
+    let mut eaten = Vec::new();
+    let mut crashed: Vec<(usize, DeathCause)> = (0..state.snakes.len())
+        .filter(|index| !state.snakes[*index].dead)
+        .filter_map(|index| {
+            let head = state.snakes[index].parts[0];
+            let length = state.snakes[index].parts.len();
+            // Check for collisions with its own body:
+            let hit_self = state.snakes[index].parts[1..].contains(&head);
+            // Check for collisions with the other snake, which a longer one eats when
+            // devouring, and which is otherwise just another wall:
+            let mut hit_other = false;
+            for (other, snake) in state.snakes.iter().enumerate() {
+                if other == index || !snake.parts.contains(&head) {
+                    continue;
+                }
+                if config.devouring && !snake.dead && length > snake.parts.len() {
+                    eaten.push((index, other));
+                } else {
+                    hit_other = true;
+                }
+            }
+            // Check for collisions with walls:
+            let hit_wall = state.walls.contains(&head) || hit_other;
+            // Check for collisions with moving walls, including one the head just swapped
+            // places with:
+            let hit_moving_wall = state
+                .moving_walls
+                .iter()
+                .any(|wall| state.snakes[index].parts.contains(&wall.position))
+                || moving_walls_before.iter().any(|wall| wall.position == head);
+            if hit_self {
+                Some((index, DeathCause::SelfBite))
+            } else if hit_wall || hit_moving_wall {
+                Some((index, DeathCause::Wall))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if state.shield_timer <= 0.0 && !config.practice() {
+        for (index, other) in eaten {
+            // Crashing into anything else at the same time comes first.
+            if crashed.iter().any(|(crashed, _)| *crashed == index) {
+                continue;
+            }
+            let growth = state.snakes[other].parts.len() as i32;
+            change_length(state, config, index, growth);
+            // Each part is worth as much as a fruit, except in rush, where only time scores.
+            if !config.rush {
+                state.score += growth as u32 * config.fruit_values.normal.points;
+                state.score_label = score_label(config.lang, state.score);
+            }
+            // One that crashed into something else as it was eaten is still eaten, but goes down
+            // to whatever it crashed into.
+            if !crashed.iter().any(|(crashed, _)| *crashed == other) {
+                crashed.push((other, DeathCause::Eaten));
+            }
+        }
+        for (index, cause) in crashed {
+            lose_life(state, config, index, cause);
+        }
+    }
+
+    for index in 0..state.snakes.len() {
+        if state.snakes[index].dead {
+            continue;
+        }
+        eat(state, config, index);
+    }
+
+    if state.is_over() {
+        state.stats_label = stats_label(config.lang, state);
+    }
+}
+
+/// Where `snake` comes out if its head moves into `entrance` and that's a portal. An exit
+/// covered by the snake's own body is closed, and the head goes into the portal as though it
+/// were an ordinary cell.
+fn portal_exit(
+    portals: Option<(Position, Position)>,
+    snake: &Snake,
+    entrance: Position,
+) -> Option<Position> {
+    let exit = match portals? {
+        (a, b) if a == entrance => b,
+        (a, b) if b == entrance => a,
+        _ => return None,
+    };
+    // The tail is left out, because it moves out of the way as the head moves in.
+    let body = &snake.parts[..snake.parts.len() - 1];
+    if body.contains(&exit) {
+        None
+    } else {
+        Some(exit)
+    }
+}
+
+/// Eats whatever is under the head of the snake at `index`.
+fn eat(state: &mut PlayState, config: &Config, index: usize) {
+    let head = state.snakes[index].parts[0];
+
+    if let Some(glowing) = state.trail.iter().position(|(cell, _)| *cell == head) {
+        state.trail.remove(glowing);
+        if !config.rush {
+            state.score += TRAIL_POINTS;
+            state.score_label = score_label(config.lang, state.score);
+        }
+    }
+
+    if let Some(fruit) = state.fruit.iter().position(|(fruit, _)| *fruit == head) {
+        let (_, kind) = state.fruit.remove(fruit);
+        let color = match kind {
+            FruitKind::Normal => ParticleColor::Fruit,
+            FruitKind::Poison => ParticleColor::Poison,
+        };
+        burst(state, head, color);
+        state.events.push(GameEvent::Ate);
+        match kind {
+            FruitKind::Normal => eat_fruit(state, config, index),
+            FruitKind::Poison => eat_poison(state, config, index),
+        }
+    }
+
+    if matches!(state.golden_fruit, Some((golden, _)) if golden == head) {
+        state.golden_fruit = None;
+        state.events.push(GameEvent::Ate);
+        shake(state, GOLDEN_SHAKE_MAGNITUDE, GOLDEN_SHAKE_SECONDS);
+        burst(state, head, ParticleColor::Golden);
+        let golden = config.fruit_values.golden;
+        change_length(state, config, index, golden.growth);
+        state.score += golden.points;
+        state.score_label = score_label(config.lang, state.score);
+    }
+
+    if let Some((_, power_up, _)) = state.power_up.filter(|(cell, _, _)| *cell == head) {
+        state.power_up = None;
+        burst(state, head, ParticleColor::PowerUp(power_up));
+        state.events.push(GameEvent::PowerUp(power_up));
+        match power_up {
+            PowerUp::Shield => state.shield_timer = SHIELD_SECONDS,
+            // Slowing down and speeding up cancel each other out, so the newest wins.
+            PowerUp::SlowMo => {
+                state.slow_mo_timer = SLOW_MO_SECONDS;
+                state.boost_timer = 0.0;
+            }
+            PowerUp::Boost => {
+                state.boost_timer = BOOST_SECONDS;
+                state.slow_mo_timer = 0.0;
+            }
+            PowerUp::Rewind => {
+                state.rewinds += 1;
+                state.rewinds_label = rewinds_label(config.lang, state.rewinds);
+            }
+        }
+    }
+
+    if state.reached_target(config) {
+        state.won = true;
+    }
+}
+
+/// How a game run by `simulate` finished.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ending {
+    Died(DeathCause),
+    ClearedBoard,
+    /// Still going when `simulate` ran out of ticks.
+    Unfinished,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameOutcome {
+    pub score: u32,
+    /// Of player one's snake.
+    pub length: usize,
+    /// How many updates it took.
+    pub ticks: u32,
+    pub ending: Ending,
+}
+
+/// Plays a whole game with no window, for tests and for comparing autopilots. Before each
+/// movement `inputs` picks player one's turn, if any. Stops after `max_ticks` movements,
+/// counting those the snake spent frozen after a crash.
+pub fn simulate(
+    config: &Config,
+    mut inputs: impl FnMut(&PlayState) -> Option<Direction>,
+    max_ticks: u32,
+) -> GameOutcome {
+    let mut state = reset_state(config, config.seed.unwrap_or(0));
+    let mut ticks = 0;
+    while ticks < max_ticks && !state.is_over() {
+        let turns: Vec<Direction> = inputs(&state).into_iter().collect();
+        let dt = move_interval(&state, config);
+        update(&mut state, config, &turns, dt);
+        ticks += 1;
+    }
+
+    let ending = if state.won {
+        Ending::ClearedBoard
+    } else if state.out_of_time {
+        Ending::Died(DeathCause::Starved)
+    } else if let Some(cause) = state.death_cause {
+        Ending::Died(cause)
+    } else {
+        Ending::Unfinished
+    };
+    GameOutcome {
+        score: state.score,
+        length: state.snakes[0].parts.len(),
+        ticks,
+        ending,
+    }
+}
+
+/// Everything the players did in one frame, and how long it lasted.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Frame {
+    pub dt: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub turns: Vec<Direction>,
+    /// Player two's turns, in a two player game.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub second_turns: Vec<Direction>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub fast_drop: bool,
+    /// Whether the autopilot was switched on or off.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub autopilot: bool,
+    /// Whether the snake was nudged faster or slower.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub faster: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub slower: bool,
+}
+
+/// A recorded game. The seed decides where every fruit lands, so playing the same frames
+/// through the same config again reproduces the game exactly.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: Config,
+    pub frames: Vec<Frame>,
+}
+
+impl Replay {
+    /// The game played so far in `state`, which was started with `config`.
+    pub fn new(state: &PlayState, config: &Config) -> Replay {
+        Replay {
+            seed: state.seed,
+            config: config.clone(),
+            frames: state.recording.clone(),
+        }
+    }
+}
+
+/// Plays one frame of input, and records it. Everything that happens in a game goes through
+/// here, so that it can be replayed.
+pub fn play_frame(state: &mut PlayState, config: &Config, frame: Frame) -> Vec<GameEvent> {
+    if frame.autopilot {
+        toggle_autopilot(state);
+    }
+    if frame.fast_drop {
+        fast_drop(state);
+    }
+    if frame.faster || frame.slower {
+        nudge_speed(state, config, frame.faster);
+    }
+    if let Some(snake) = state.snakes.get_mut(1) {
+        for turn in frame.second_turns.iter().cloned() {
+            queue_turn(snake, turn);
+        }
+    }
+    let events = update(state, config, &frame.turns, frame.dt);
+    state.recording.push(frame);
+    events
+}
+
+/// Plays `replay` through from the start, and returns how the game ended up.
+pub fn replay(replay: &Replay) -> PlayState {
+    let mut state = reset_state(&replay.config, replay.seed);
+    for frame in replay.frames.iter() {
+        play_frame(&mut state, &replay.config, frame.clone());
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A default board with the snake and fruit placed by hand.
+    fn state_with(parts: Vec<Position>, direction: Direction, fruit: Position) -> PlayState {
+        let mut state = reset_state(&Config::default(), 0);
+        // On the last life, so that crashing ends the game.
+        state.lives = 1;
+        state.snakes[0].parts = parts;
+        state.snakes[0].direction = direction;
+        state.fruit = vec![(fruit, FruitKind::Normal)];
+        state
+    }
+
+    fn step_events(state: &mut PlayState) -> Vec<GameEvent> {
+        let config = Config::default();
+        update(state, &config, &[], config.tick_seconds)
+    }
+
+    fn step(state: &mut PlayState, turns: &[Direction]) {
+        let config = Config::default();
+        update(state, &config, turns, config.tick_seconds);
+    }
+
+    #[test]
+    fn running_into_a_wall_is_fatal() {
+        let mut state = state_with(
+            vec![Position((1, 1)), Position((2, 1))],
+            Direction::West,
+            Position((5, 5)),
+        );
+
+        step(&mut state, &[]);
+
+        assert_eq!(state.death_cause, Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn crashing_with_lives_to_spare_respawns_at_the_start() {
+        let mut state = state_with(
+            vec![
+                Position((1, 5)),
+                Position((2, 5)),
+                Position((3, 5)),
+                Position((4, 5)),
+                Position((5, 5)),
+                Position((6, 5)),
+                Position((7, 5)),
+            ],
+            Direction::West,
+            Position((8, 8)),
+        );
+        state.lives = 2;
+
+        step(&mut state, &[]);
+
+        assert!(!state.is_dead());
+        assert_eq!(state.lives, 1);
+        assert_eq!(state.snakes[0].parts.len(), RESPAWN_MAX_LENGTH);
+        let start = start(&Config::default())[0];
+        assert_eq!(state.snakes[0].parts[0], start);
+        assert_eq!(state.snakes[0].direction, Direction::East);
+
+        // It waits for the flashing to finish before it sets off again.
+        step(&mut state, &[]);
+        assert_eq!(state.snakes[0].parts[0], start);
+        for _ in 0..10 {
+            step(&mut state, &[]);
+        }
+        assert!(state.snakes[0].parts[0] != start);
+    }
+
+    #[test]
+    fn updates_say_what_happened() {
+        let mut state = state_with(
+            vec![Position((7, 1)), Position((6, 1))],
+            Direction::East,
+            Position((8, 1)),
+        );
+        state.lives = 2;
+
+        assert_eq!(step_events(&mut state), vec![GameEvent::Ate]);
+        assert_eq!(step_events(&mut state), Vec::new());
+        assert_eq!(
+            step_events(&mut state),
+            vec![GameEvent::Crashed(DeathCause::Wall)]
+        );
+
+        state.respawn_timer = 0.0;
+        state.snakes[0].parts = vec![Position((9, 5)), Position((8, 5))];
+        assert_eq!(
+            step_events(&mut state),
+            vec![GameEvent::Died(DeathCause::Wall)]
+        );
+    }
+
+    #[test]
+    fn toasts_pile_up_and_fade() {
+        let mut state = reset_state(&Config::default(), 0);
+        for toast in 0..=MAX_TOASTS {
+            push_toast(&mut state, toast.to_string());
+        }
+
+        assert_eq!(state.toasts.len(), MAX_TOASTS);
+        assert_eq!(state.toasts[0].text, "1");
+        update_effects(&mut state, TOAST_SECONDS);
+        assert!(state.toasts.is_empty());
+    }
+
+    #[test]
+    fn running_into_the_body_is_fatal() {
+        let mut state = state_with(
+            vec![
+                Position((3, 3)),
+                Position((3, 2)),
+                Position((4, 2)),
+                Position((4, 3)),
+                Position((4, 4)),
+            ],
+            Direction::East,
+            Position((8, 8)),
+        );
+
+        step(&mut state, &[]);
+
+        assert_eq!(state.death_cause, Some(DeathCause::SelfBite));
+    }
+
+    #[test]
+    fn growing_to_the_target_length_wins() {
+        let config = Config {
+            target_length: Some(4),
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![Position((2, 5)), Position((1, 5))],
+            Direction::East,
+            Position((3, 5)),
+        );
+
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert!(!state.is_over());
+        state.fruit = vec![(Position((4, 5)), FruitKind::Normal)];
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert!(state.won);
+        assert!(!state.is_dead());
+    }
+
+    #[test]
+    fn coming_back_to_the_fruit_trail_scores_before_it_fades() {
+        let config = Config {
+            fruit_trail: true,
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![Position((3, 3)), Position((2, 3))],
+            Direction::East,
+            Position((4, 3)),
+        );
+
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(state.trail, vec![(Position((4, 3)), TRAIL_SECONDS)]);
+        let score = state.score;
+        state.fruit = vec![(Position((8, 8)), FruitKind::Normal)];
+        for turn in [Direction::North, Direction::West, Direction::South] {
+            update(&mut state, &config, &[turn], config.tick_seconds);
+        }
+        assert_eq!(state.snakes[0].parts[0], Position((3, 3)));
+        update(&mut state, &config, &[Direction::East], config.tick_seconds);
+
+        // Back through the glowing cell, which isn't there to be scored again.
+        assert!(!state.is_dead());
+        assert_eq!(state.score, score + TRAIL_POINTS);
+        assert!(state.trail.is_empty());
+    }
+
+    #[test]
+    fn practice_waits_at_walls_and_crosses_the_body() {
+        let config = Config {
+            practice: true,
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![
+                Position((3, 3)),
+                Position((3, 2)),
+                Position((4, 2)),
+                Position((4, 3)),
+                Position((4, 4)),
+            ],
+            Direction::East,
+            Position((8, 8)),
+        );
+
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(state.snakes[0].parts[0], Position((4, 3)));
+        assert!(!state.is_dead());
+
+        state.snakes[0].parts = vec![Position((1, 1)), Position((2, 1))];
+        state.snakes[0].direction = Direction::West;
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(state.snakes[0].parts[0], Position((1, 1)));
+        update(
+            &mut state,
+            &config,
+            &[Direction::North],
+            config.tick_seconds,
+        );
+        assert_eq!(state.snakes[0].parts[0], Position((1, 2)));
+        assert_eq!(state.lives, 1);
+    }
+
+    #[test]
+    fn hardcore_is_one_life_without_help() {
+        let config = Config {
+            hardcore: true,
+            practice: true,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 0);
+        assert_eq!(state.lives, 1);
+        assert!(!config.practice());
+
+        let mut spawned = 0;
+        for _ in 0..200 {
+            state.snakes[0].parts.truncate(3);
+            state.power_up = None;
+            eat_fruit(&mut state, &config, 0);
+            if let Some((_, power_up, _)) = state.power_up {
+                assert_eq!(power_up, PowerUp::Boost);
+                spawned += 1;
+            }
+        }
+        assert!(spawned > 0);
+    }
+
+    #[test]
+    fn wide_boards_fit_without_stretching() {
+        let config = Config {
+            board_width: 30,
+            board_height: 10,
+            wrap_mode: WrapMode::Walls,
+            ..Config::default()
+        };
+
+        // However the window is shaped, the board fills one side and fits in the other.
+        for (width, height) in [(800.0, 600.0), (600.0, 800.0), (3000.0, 100.0)] {
+            let cell_size = cell_size_to_fit(&config, width, height);
+            let (board_width, board_height) = (30.0 * cell_size, 10.0 * cell_size);
+            assert!(board_width <= width + 0.001 && board_height <= height + 0.001);
+            assert!((board_width - width).abs() < 0.001 || (board_height - height).abs() < 0.001);
+        }
+        assert_eq!(cell_size_to_fit(&config, 3000.0, 100.0), 10.0);
+
+        // The border runs around all four sides, and nothing starts outside it.
+        let state = reset_state(&config, 0);
+        assert_eq!(state.walls.len(), 2 * 30 + 2 * 8);
+        assert!(state.walls.contains(&Position((29, 9))));
+        assert!(!state.walls.contains(&Position((28, 8))));
+        assert!(state.snakes[0]
+            .parts
+            .iter()
+            .all(|&part| on_board(&config, part)));
+        assert!(state
+            .fruit
+            .iter()
+            .all(|&(fruit, _)| on_board(&config, fruit)));
+    }
+
+    #[test]
+    fn two_players_only_fit_where_their_snakes_start_apart() {
+        let board = |board_width, board_height| Config {
+            board_width,
+            board_height,
+            two_player: true,
+            ..Config::default()
+        };
+        assert!(two_players_fit(&Config::default()));
+        // The snakes share the middle row of the smallest boards, and meet head on at first.
+        assert!(!two_players_fit(&board(5, 3)));
+        assert!(!two_players_fit(&board(7, 3)));
+
+        let config = board(9, 3);
+        assert!(two_players_fit(&config));
+        let mut state = reset_state(&config, 0);
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert!(state.snakes.iter().all(|snake| !snake.dead));
+    }
+
+    #[test]
+    fn devouring_snakes_eat_shorter_ones_and_crash_into_their_equals() {
+        let config = Config {
+            two_player: true,
+            devouring: true,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 0);
+        state.fruit = vec![(Position((8, 1)), FruitKind::Normal)];
+        state.snakes[0].parts = vec![
+            Position((3, 5)),
+            Position((3, 4)),
+            Position((3, 3)),
+            Position((3, 2)),
+        ];
+        state.snakes[0].direction = Direction::North;
+        state.snakes[1].parts = vec![Position((4, 6)), Position((3, 6)), Position((2, 6))];
+        state.snakes[1].direction = Direction::East;
+        state.snakes[1].pending_turns.clear();
+
+        // Player one runs into player two's tail, and is the longer of the two.
+        let events = update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(events, vec![GameEvent::Died(DeathCause::Eaten)]);
+        assert!(state.snakes[1].dead);
+        assert!(!state.snakes[0].dead);
+        assert_eq!(state.snakes[0].parts.len(), 7);
+        assert_eq!(state.score, 3 * config.fruit_values.normal.points);
+        assert_eq!(state.score_label, score_label(Lang::English, state.score));
+
+        // It's eaten all the same if it crashes into a wall as it goes.
+        let mut state = reset_state(&config, 0);
+        state.fruit = vec![(Position((8, 1)), FruitKind::Normal)];
+        state.snakes[0].parts = vec![
+            Position((3, 5)),
+            Position((3, 4)),
+            Position((3, 3)),
+            Position((3, 2)),
+        ];
+        state.snakes[0].direction = Direction::North;
+        state.snakes[1].parts = vec![Position((4, 6)), Position((3, 6)), Position((2, 6))];
+        state.snakes[1].direction = Direction::East;
+        state.snakes[1].pending_turns.clear();
+        state.walls.push(Position((5, 6)));
+        let events = update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(events, vec![GameEvent::Died(DeathCause::Wall)]);
+        assert!(state.snakes[1].dead);
+        assert_eq!(state.snakes[0].parts.len(), 7);
+        assert_eq!(state.score, 3 * config.fruit_values.normal.points);
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert!(!state.snakes[0].dead);
+
+        let mut state = reset_state(&config, 0);
+        state.fruit = vec![(Position((8, 1)), FruitKind::Normal)];
+        state.snakes[0].parts = vec![Position((3, 5)), Position((2, 5))];
+        state.snakes[0].direction = Direction::East;
+        state.snakes[1].parts = vec![Position((5, 5)), Position((6, 5))];
+        state.snakes[1].direction = Direction::West;
+        state.snakes[1].pending_turns.clear();
+
+        // Meeting head on, neither is longer.
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert!(state.is_dead());
+        assert_eq!(state.death_cause, Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn fruit_values_set_what_each_fruit_does() {
+        let config = Config {
+            fruit_values: FruitValues {
+                normal: FruitValue {
+                    points: 0,
+                    growth: 3,
+                    weight: 0,
+                },
+                poison: FruitValue {
+                    points: 5,
+                    growth: -1,
+                    weight: 1,
+                },
+                golden: FruitValue {
+                    weight: 0,
+                    ..FruitValues::default().golden
+                },
+            },
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 0);
+        state.snakes[0].parts = vec![Position((2, 1)), Position((1, 1))];
+        state.fruit = vec![(Position((3, 1)), FruitKind::Normal)];
+
+        // Only poison has any weight, so it comes out with every fruit.
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(state.snakes[0].parts.len(), 5);
+        assert_eq!(state.score, fruit_points(0, config.tick_seconds));
+        assert!(state.golden_fruit.is_none());
+        let poison = state
+            .fruit
+            .iter()
+            .position(|(_, kind)| *kind == FruitKind::Poison)
+            .expect("poison to come out");
+
+        state.fruit[poison].0 = Position((4, 1));
+        let score = state.score;
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(state.snakes[0].parts.len(), 4);
+        assert_eq!(state.score, score + 5);
+
+        let mut rng = Rng::new(0);
+        assert!((0..20).all(|_| rng.pick_weighted(&[0, 0, 1]) == 2));
+        assert_eq!(rng.pick_weighted(&[0, 0]), 0);
+    }
+
+    #[test]
+    fn rewinds_undo_the_next_crash() {
+        let mut state = state_with(
+            vec![Position((8, 5)), Position((7, 5))],
+            Direction::East,
+            Position((2, 2)),
+        );
+        state.power_up = Some((Position((9, 5)), PowerUp::Rewind, POWER_UP_SECONDS));
+
+        step(&mut state, &[]);
+        assert_eq!(state.rewinds, 1);
+        // Into the border, which is taken back.
+        let events = step_events(&mut state);
+        assert_eq!(events, vec![GameEvent::Rewound]);
+        assert!(!state.is_dead());
+        assert_eq!(state.rewinds, 0);
+        assert_eq!(state.lives, 1);
+        assert_eq!(
+            state.snakes[0].parts,
+            vec![Position((9, 5)), Position((8, 5))]
+        );
+        assert!(state.history.is_empty());
+        assert_eq!(
+            state.toasts.back().map(|toast| toast.text.as_str()),
+            Some("Rewound!")
+        );
+    }
+
+    #[test]
+    fn with_two_players_the_longest_snake_left_wins() {
+        let config = Config {
+            two_player: true,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 0);
+        state.snakes[0].parts = vec![Position((3, 5)), Position((2, 5)), Position((1, 5))];
+        state.snakes[0].direction = Direction::North;
+        state.snakes[1].parts = vec![Position((3, 3)), Position((4, 3))];
+        state.snakes[1].direction = Direction::West;
+        state.snakes[1].pending_turns.clear();
+        state.fruit = vec![(Position((8, 1)), FruitKind::Normal)];
+
+        // Player two turns into player one's body, and is out.
+        queue_turn(&mut state.snakes[1], Direction::North);
+        update(&mut state, &config, &[], config.tick_seconds);
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert!(state.snakes[1].dead);
+        assert!(!state.snakes[0].dead);
+        assert!(!state.is_dead());
+
+        // Player one carries on until they crash too.
+        while !state.is_dead() {
+            update(&mut state, &config, &[], config.tick_seconds);
+        }
+        assert!(state.snakes[0].dead);
+        assert!(state.winner_label.starts_with("PLAYER ONE WINS"));
+    }
+
+    #[test]
+    fn labels_are_worded_in_the_chosen_language() {
+        let config = Config {
+            lang: Lang::Spanish,
+            seed: Some(7),
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 7);
+        assert_eq!(state.snakes[0].length_label, "longitud de 2");
+        assert_eq!(state.seed_label, "semilla 7");
+
+        state.elapsed = 65.0;
+        state.fruit_eaten = 3;
+        state.turns = 4;
+        assert_eq!(
+            stats_label(config.lang, &state),
+            "comiste 3 frutas en 1:05, girando 4 veces"
+        );
+        assert_eq!(
+            stats_label(Lang::English, &state),
+            "ate 3 fruit in 1:05, turning 4 times"
+        );
+    }
+
+    #[test]
+    fn levels_are_read_from_text() {
+        let level = parse_level("#####\n#..F#\n#.S.#\n#####\n").unwrap();
+        assert_eq!((level.width, level.height), (5, 4));
+        assert_eq!(level.start, Position((2, 1)));
+        assert_eq!(level.fruit_spawns, vec![Position((3, 2))]);
+        assert!(level.walls.contains(&Position((0, 3))));
+        assert!(!level.walls.contains(&Position((1, 1))));
+
+        let config = with_level(&Config::default(), level);
+        let state = reset_state(&config, 1);
+        assert_eq!(
+            state.snakes[0].parts,
+            vec![Position((2, 1)), Position((1, 1))]
+        );
+        assert_eq!(state.fruit, vec![(Position((3, 2)), FruitKind::Normal)]);
+        assert_eq!(state.walls.len(), 14);
+    }
+
+    #[test]
+    fn broken_levels_say_what_is_wrong() {
+        let error = |text| parse_level(text).unwrap_err();
+        assert!(error("#####\n#...#\n#####").contains("no start"));
+        assert!(
+            error("#####\n#.SS#\n#...#\n#####").contains("line 2, column 3 and line 2, column 4")
+        );
+        assert!(error("#####\n#.S#\n#####").contains("line 2 is 4 wide"));
+        assert!(error("#####\n#.Sx#\n#####").contains("line 2, column 4"));
+        assert!(error("#####\n##S.#\n#...#\n#####").contains("west"));
+        assert!(error("S....\n.....\n.....").contains("edge"));
+    }
+
+    #[test]
+    fn the_shrinking_board_closes_in_and_crushes() {
+        let config = Config {
+            shrinking: true,
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![Position((5, 5)), Position((4, 5))],
+            Direction::North,
+            Position((1, 9)),
+        );
+        let dt = state.tick_interval;
+        state.shrink_interval = dt;
+
+        update(&mut state, &config, &[], dt);
+
+        // The first ring sits just inside the border, which took the fruit with it.
+        assert_eq!(state.rings, 1);
+        assert!(state.walls.contains(&Position((1, 1))));
+        assert!(state.walls.contains(&Position((9, 5))));
+        assert!(!state.walls.contains(&Position((2, 2))));
+        assert!(!state.walls.contains(&state.fruit[0].0));
+        assert_eq!(state.score, SHRINK_POINTS);
+        assert!(!state.is_dead());
+
+        // Heading north, the snake is caught by the next ring.
+        state.snakes[0].parts = vec![Position((5, 8)), Position((5, 7))];
+        update(&mut state, &config, &[], config.shrink_seconds);
+        assert_eq!(state.rings, 2);
+        assert!(state.is_dead());
+    }
+
+    #[test]
+    fn portals_carry_the_head_across_and_then_move() {
+        let config = Config {
+            portals: true,
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.portals = Some((Position((3, 1)), Position((5, 7))));
+
+        update(&mut state, &config, &[], config.tick_seconds);
+
+        assert_eq!(state.snakes[0].parts[0], Position((5, 7)));
+        assert_eq!(state.snakes[0].direction, Direction::East);
+        assert!(state.portals.is_some());
+        assert_ne!(state.portals, Some((Position((3, 1)), Position((5, 7)))));
+
+        // An exit under the snake's own body is closed.
+        let snake = &state.snakes[0];
+        assert_eq!(
+            portal_exit(
+                Some((Position((6, 7)), Position((5, 7)))),
+                snake,
+                Position((6, 7))
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn moving_walls_bounce_and_are_fatal() {
+        let mut state = state_with(
+            vec![Position((2, 5)), Position((1, 5))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.moving_walls = vec![MovingWall {
+            position: Position((8, 5)),
+            direction: Direction::East,
+        }];
+
+        // It turns back at the border, and comes to meet the snake.
+        step(&mut state, &[]);
+        assert_eq!(state.moving_walls[0].position, Position((9, 5)));
+        step(&mut state, &[]);
+        assert_eq!(state.moving_walls[0].position, Position((8, 5)));
+        assert_eq!(state.moving_walls[0].direction, Direction::West);
+        assert!(!state.is_dead());
+
+        step(&mut state, &[]);
+        step(&mut state, &[]);
+        assert!(state.is_dead());
+    }
+
+    #[test]
+    fn meeting_the_quota_moves_on_to_the_next_level() {
+        let config = Config {
+            campaign: true,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 0);
+        state.fruit_quota = 1;
+        state.snakes[0].parts = vec![Position((5, 5)), Position((4, 5))];
+        state.fruit = vec![(Position((6, 5)), FruitKind::Normal)];
+
+        update(&mut state, &config, &[], LEVELS[0].tick_seconds);
+
+        assert_eq!(state.level, 2);
+        assert_eq!(state.fruit_quota, LEVELS[1].fruit_quota);
+        assert_eq!(
+            state.score,
+            fruit_points(FRUIT_POINTS, LEVELS[0].tick_seconds)
+        );
+        assert_eq!(state.snakes[0].parts, start(&config));
+        // Level two has pillars.
+        assert!(state.walls.contains(&Position((3, 3))));
+
+        // Nothing moves while the level is announced.
+        update(&mut state, &config, &[], LEVELS[1].tick_seconds);
+        assert_eq!(state.snakes[0].parts, start(&config));
+
+        // Clearing the last level wins.
+        state.level = LEVELS.len() as u32;
+        state.fruit_quota = 1;
+        state.level_timer = 0.0;
+        state.fruit = vec![(Position((3, 1)), FruitKind::Normal)];
+        let dt = state.tick_interval;
+        update(&mut state, &config, &[], dt);
+        assert!(state.won);
+    }
+
+    #[test]
+    fn time_attack_ends_when_the_clock_runs_out() {
+        // Wrapping around an open board, so the snake can keep moving until the clock runs out.
+        let config = Config {
+            time_attack: true,
+            wrap_mode: WrapMode::Wrap,
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![Position((2, 5)), Position((1, 5))],
+            Direction::East,
+            Position((3, 5)),
+        );
+        state.walls.clear();
+        state.time_remaining = 1.0;
+
+        update(&mut state, &config, &[], config.tick_seconds);
+        assert_eq!(state.time_remaining, 0.8 + TIME_BONUS_SECONDS);
+        assert_eq!(state.time_label, "0:03");
+
+        state.fruit = vec![(Position((8, 8)), FruitKind::Normal)];
+        update(&mut state, &config, &[], 2.0);
+        assert!(!state.is_over());
+        update(&mut state, &config, &[], 1.0);
+        assert!(state.out_of_time);
+        assert!(!state.is_dead());
+    }
+
+    #[test]
+    fn rush_only_gets_faster_and_scores_time() {
+        let config = Config {
+            rush: true,
+            layout: Layout::Cross,
+            moving_walls: 2,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 0);
+        state.lives = 1;
+        assert!(state.walls.is_empty());
+        assert!(state.moving_walls.is_empty());
+
+        let mut last_tick = state.tick_interval;
+        for _ in 0..480 {
+            // Kept out of the way, so the snake just goes round and round its row.
+            state.fruit = vec![(Position((5, 5)), FruitKind::Normal)];
+            update(&mut state, &config, &[], 0.125);
+            assert!(state.tick_interval <= last_tick);
+            last_tick = state.tick_interval;
+        }
+
+        assert!(!state.is_over());
+        assert!(state.tick_interval < config.tick_seconds / 2.0);
+        assert_eq!(state.score, 60);
+        assert_eq!(state.score_label, score_label(config.lang, 60));
+    }
+
+    #[test]
+    fn eating_fruit_grows_by_one() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((3, 1)),
+        );
+
+        assert_eq!(state.fruit_spawn_time, None);
+        step(&mut state, &[]);
+
+        assert!(!state.is_dead());
+        assert_eq!(state.snakes[0].parts.len(), 3);
+        // The new fruit grows in from when it appeared.
+        assert_eq!(state.fruit_spawn_time, Some(state.elapsed));
+        // It went on one of the cells left free, all of which are still free but that one.
+        let free_cells = state.free_cells(&Config::default()).len();
+        assert_eq!(state.fruit_choices, free_cells + 1);
+    }
+
+    #[test]
+    fn snakes_start_as_long_as_asked_when_they_fit() {
+        let config = Config {
+            start_length: 5,
+            two_player: true,
+            ..Config::default()
+        };
+        let state = reset_state(&config, 0);
+        let one = &state.snakes[0].parts;
+        assert_eq!(one.len(), 5);
+        assert_eq!(one[0], Position((5, 1)));
+        assert_eq!(one[4], START_TAIL);
+        let two = &state.snakes[1].parts;
+        assert_eq!(two.len(), 5);
+        assert_eq!(state.snakes[1].direction, Direction::West);
+        for part in one.iter().chain(two) {
+            assert!(!state.walls.contains(part));
+        }
+        // Every segment is right behind the one before it.
+        for pair in one.windows(2) {
+            assert_eq!(pair[1], next_position(pair[0], Direction::West, &config));
+        }
+
+        // Too long for the board, so it stops short of the far wall.
+        let config = Config {
+            board_width: 6,
+            board_height: 6,
+            start_length: 50,
+            ..Config::default()
+        };
+        let state = reset_state(&config, 0);
+        let parts = &state.snakes[0].parts;
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], Position((3, 1)));
+        assert!(parts.iter().all(|part| !state.walls.contains(part)));
+    }
+
+    #[test]
+    fn growing_never_lands_on_another_cell() {
+        // Heading each way, with the tail bent round a corner, so the direction of travel
+        // says nothing about where the tail is.
+        for (direction, parts, fruit) in [
+            (Direction::North, [(5, 5), (5, 4), (4, 4)], (5, 6)),
+            (Direction::South, [(5, 5), (5, 6), (4, 6)], (5, 4)),
+            (Direction::East, [(5, 5), (5, 4), (4, 4)], (6, 5)),
+            (Direction::West, [(5, 5), (5, 4), (6, 4)], (4, 5)),
+        ] {
+            let parts: Vec<Position> = parts.into_iter().map(Position).collect();
+            let mut state = state_with(parts.clone(), direction, Position(fruit));
+
+            step(&mut state, &[]);
+            let grown = &state.snakes[0].parts;
+            assert_eq!(grown.len(), 4);
+            // The new segment waits on the tail, somewhere the snake already was.
+            assert_eq!(grown[3], grown[2]);
+            assert!(parts.contains(&grown[3]));
+
+            // Then it's left behind as the snake moves on, with every segment on its own cell.
+            state.fruit = vec![(Position((9, 9)), FruitKind::Normal)];
+            step(&mut state, &[]);
+            let parts = &state.snakes[0].parts;
+            assert!(!state.is_dead());
+            for (index, part) in parts.iter().enumerate() {
+                assert!(!parts[index + 1..].contains(part));
+            }
+        }
+    }
+
+    #[test]
+    fn fruit_can_go_anywhere_inside_the_border() {
+        let config = Config::default();
+        let mut state = reset_state(&config, 0);
+        state.fruit.clear();
+
+        let free_cells = state.free_cells(&config);
+        let interior = (config.board_width - 2) * (config.board_height - 2);
+        assert_eq!(
+            free_cells.len(),
+            interior as usize - state.snakes[0].parts.len()
+        );
+        let far_corner = Position((
+            config.board_width as i32 - 2,
+            config.board_height as i32 - 2,
+        ));
+        assert!(free_cells.contains(&far_corner));
+        assert!(free_cells.contains(&Position((config.board_width as i32 - 2, 1))));
+    }
+
+    #[test]
+    fn golden_fruit_grows_by_two_and_expires() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.golden_fruit = Some((Position((3, 1)), GOLDEN_FRUIT_SECONDS));
+
+        step(&mut state, &[]);
+
+        assert_eq!(state.snakes[0].parts.len(), 4);
+        assert_eq!(state.golden_fruit, None);
+
+        state.golden_fruit = Some((Position((8, 1)), 0.1));
+        step(&mut state, &[]);
+        assert_eq!(state.golden_fruit, None);
+    }
+
+    #[test]
+    fn a_shield_passes_through_walls_until_it_runs_out() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((3, 1))],
+            Direction::West,
+            Position((8, 8)),
+        );
+        state.power_up = Some((Position((1, 1)), PowerUp::Shield, POWER_UP_SECONDS));
+
+        step(&mut state, &[]);
+        assert!(state.shield_timer > 0.0);
+        step(&mut state, &[]);
+        step(&mut state, &[]);
+
+        step(&mut state, &[]);
+
+        // Through the left wall and back in through the right one.
+        assert!(!state.is_dead());
+        assert_eq!(state.snakes[0].parts[0], Position((9, 1)));
+
+        state.shield_timer = 0.0;
+        step(&mut state, &[Direction::North]);
+        assert!(!state.is_dead());
+        step(&mut state, &[Direction::East]);
+        assert!(state.is_dead());
+    }
+
+    #[test]
+    fn a_boost_squeezes_the_interval_and_cancels_slow_mo() {
+        let config = Config::default();
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.tick_interval = 0.1;
+        state.slow_mo_timer = SLOW_MO_SECONDS;
+        state.power_up = Some((Position((3, 1)), PowerUp::Boost, POWER_UP_SECONDS));
+
+        update(&mut state, &config, &[], 0.1 * config.slow_mo_factor);
+        assert_eq!(state.snakes[0].parts[0], Position((3, 1)));
+        assert_eq!(state.slow_mo_timer, 0.0);
+        assert_eq!(state.boost_timer, BOOST_SECONDS);
+        assert_eq!(move_interval(&state, &config), 0.1 * BOOST_FACTOR);
+
+        // Two moves in the time one would usually take.
+        update(&mut state, &config, &[], 0.05);
+        update(&mut state, &config, &[], 0.05);
+        assert_eq!(state.snakes[0].parts[0], Position((5, 1)));
+
+        // And slow-mo cancels the boost in turn.
+        state.power_up = Some((Position((6, 1)), PowerUp::SlowMo, POWER_UP_SECONDS));
+        update(&mut state, &config, &[], 0.05);
+        assert_eq!(state.boost_timer, 0.0);
+        assert!(state.slow_mo_timer > 0.0);
+    }
+
+    #[test]
+    fn slow_mo_stretches_the_ramped_interval() {
+        let config = Config::default();
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.tick_interval = 0.1;
+        state.power_up = Some((Position((3, 1)), PowerUp::SlowMo, POWER_UP_SECONDS));
+
+        update(&mut state, &config, &[], 0.1);
+        assert!(state.slow_mo_timer > 0.0);
+        assert_eq!(move_interval(&state, &config), 0.1 * config.slow_mo_factor);
+
+        update(&mut state, &config, &[], 0.1);
+        assert_eq!(state.snakes[0].parts[0], Position((3, 1)));
+        update(&mut state, &config, &[], 0.1);
+        assert_eq!(state.snakes[0].parts[0], Position((4, 1)));
+    }
+
+    #[test]
+    fn the_shake_after_a_crash_settles_completely() {
+        let mut state = state_with(
+            vec![Position((1, 1)), Position((2, 1))],
+            Direction::West,
+            Position((5, 5)),
+        );
+
+        step(&mut state, &[]);
+        assert!(state.shake_magnitude > 0.0);
+
+        for _ in 0..100 {
+            update_effects(&mut state, 0.016);
+        }
+        assert_eq!(state.shake_magnitude, 0.0);
+        assert_eq!(state.shake_timer, 0.0);
+    }
+
+    #[test]
+    fn particles_are_capped_and_fade_away() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((3, 1)),
+        );
+        for _ in 0..MAX_PARTICLES {
+            burst(&mut state, Position((5, 5)), ParticleColor::Fruit);
+        }
+        assert_eq!(state.particles.len(), MAX_PARTICLES);
+
+        update_effects(&mut state, PARTICLE_SECONDS);
+        assert!(state.particles.is_empty());
+    }
+
+    #[test]
+    fn poison_shrinks_the_snake() {
+        let mut state = state_with(
+            vec![
+                Position((4, 1)),
+                Position((3, 1)),
+                Position((2, 1)),
+                Position((1, 1)),
+            ],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.fruit.push((Position((5, 1)), FruitKind::Poison));
+
+        step(&mut state, &[]);
+
+        assert!(!state.is_dead());
+        assert_eq!(state.snakes[0].parts.len(), 2);
+    }
+
+    #[test]
+    fn poison_kills_a_snake_too_short_to_shrink() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.fruit.push((Position((3, 1)), FruitKind::Poison));
+
+        step(&mut state, &[]);
+
+        assert_eq!(state.death_cause, Some(DeathCause::Poison));
+    }
+
+    #[test]
+    fn eating_quickly_scores_more() {
+        assert_eq!(
+            fruit_points(FRUIT_POINTS, 0.0),
+            FRUIT_POINTS + MAX_SPEED_BONUS as u32
+        );
+        assert!(fruit_points(FRUIT_POINTS, 1.5) < fruit_points(FRUIT_POINTS, 0.5));
+        assert_eq!(fruit_points(FRUIT_POINTS, 60.0), FRUIT_POINTS);
+    }
+
+    #[test]
+    fn fruit_eaten_close_together_builds_a_combo() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((3, 1)),
+        );
+
+        step(&mut state, &[]);
+        assert_eq!(state.combo, 1);
+        let first = state.score;
+
+        state.fruit = vec![(Position((5, 1)), FruitKind::Normal)];
+        step(&mut state, &[]);
+        step(&mut state, &[]);
+        assert_eq!(state.combo, 2);
+        assert_eq!(state.score - first, fruit_points(FRUIT_POINTS, 0.4) * 2);
+
+        // Wandering off for too long loses it.
+        state.fruit = vec![(Position((8, 8)), FruitKind::Normal)];
+        state.ticks_since_fruit = Config::default().combo_ticks;
+        step(&mut state, &[Direction::North]);
+        assert!(!state.is_dead());
+        assert_eq!(state.combo, 1);
+    }
+
+    #[test]
+    fn eating_fruit_speeds_the_snake_up() {
+        let mut state = state_with(
+            vec![Position((2, 1)), Position((1, 1))],
+            Direction::East,
+            Position((3, 1)),
+        );
+        let before = state.tick_interval;
+
+        step(&mut state, &[]);
+
+        assert!(state.tick_interval < before);
+        assert_eq!(
+            ramped_tick_interval(Difficulty::Normal, 0.2, 1000),
+            MIN_TICK_SECONDS
+        );
+    }
+
+    #[test]
+    fn harder_games_are_faster_and_busier() {
+        let presets = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+        let states = presets.map(|difficulty| {
+            let config = Config {
+                board_width: 15,
+                board_height: 15,
+                layout: Layout::Random,
+                difficulty,
+                ..Config::default()
+            };
+            reset_state(&config, 3)
+        });
+        for pair in states.windows(2) {
+            assert!(pair[0].tick_interval > pair[1].tick_interval);
+            assert!(pair[0].walls.len() < pair[1].walls.len());
+        }
+        assert_eq!(states[1].tick_interval, Config::default().tick_seconds);
+
+        // Every fruit speeds a hard game up by more.
+        let slowed = |difficulty: Difficulty| {
+            ramped_tick_interval(difficulty, 0.2, 1) / ramped_tick_interval(difficulty, 0.2, 0)
+        };
+        assert!(slowed(Difficulty::Easy) > slowed(Difficulty::Normal));
+        assert!(slowed(Difficulty::Normal) > slowed(Difficulty::Hard));
+
+        assert_eq!(Difficulty::Easy.easier(), Difficulty::Easy);
+        assert_eq!(Difficulty::Hard.harder(), Difficulty::Hard);
+        assert_eq!(Difficulty::Normal.harder(), Difficulty::Hard);
+    }
+
+    #[test]
+    fn turns_and_time_are_counted() {
+        let mut state = state_with(
+            vec![Position((3, 3)), Position((2, 3))],
+            Direction::East,
+            Position((8, 8)),
+        );
+
+        step(&mut state, &[Direction::North]);
+        step(&mut state, &[]);
+        step(&mut state, &[Direction::North, Direction::West]);
+
+        assert_eq!(state.turns, 2);
+        assert!((state.elapsed - Config::default().tick_seconds * 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn two_quick_turns_cannot_reverse_the_snake() {
+        let mut state = state_with(
+            vec![Position((3, 3)), Position((2, 3)), Position((1, 3))],
+            Direction::East,
+            Position((8, 8)),
+        );
+
+        update(
+            &mut state,
+            &Config::default(),
+            &[Direction::North, Direction::West],
+            0.0,
+        );
+        step(&mut state, &[]);
+        step(&mut state, &[]);
+
+        assert!(!state.is_dead());
+        assert_eq!(state.snakes[0].direction, Direction::West);
+    }
+
+    #[test]
+    fn only_two_turns_are_queued_at_once() {
+        let mut state = state_with(
+            vec![Position((3, 3)), Position((2, 3))],
+            Direction::East,
+            Position((8, 8)),
+        );
+
+        update(
+            &mut state,
+            &Config::default(),
+            &[Direction::South, Direction::East, Direction::North],
+            0.0,
+        );
+
+        assert_eq!(
+            state.snakes[0].pending_turns,
+            [Direction::South, Direction::East]
+        );
+        step(&mut state, &[]);
+        step(&mut state, &[]);
+        assert_eq!(state.snakes[0].parts[0], Position((4, 2)));
+    }
+
+    #[test]
+    fn nudged_speeds_stay_in_bounds_and_last() {
+        let config = Config::default();
+        let mut state = state_with(
+            vec![Position((3, 3)), Position((2, 3))],
+            Direction::East,
+            Position((4, 3)),
+        );
+        let before = state.tick_interval;
+
+        nudge_speed(&mut state, &config, false);
+        assert!((state.tick_interval - before - TICK_NUDGE).abs() < 1e-6);
+        assert_eq!(
+            state.speed_label,
+            speed_label(config.lang, state.tick_interval)
+        );
+
+        // Eating still speeds the snake up, from the slower pace.
+        let dt = state.tick_interval;
+        update(&mut state, &config, &[], dt);
+        assert_eq!(state.fruit_eaten, 1);
+        let ramped = ramped_tick_interval(config.difficulty, config.tick_seconds, 1);
+        assert!((state.tick_interval - ramped - TICK_NUDGE).abs() < 1e-6);
+
+        for _ in 0..100 {
+            nudge_speed(&mut state, &config, false);
+        }
+        assert_eq!(state.tick_interval, MAX_NUDGED_TICK);
+        for _ in 0..100 {
+            nudge_speed(&mut state, &config, true);
+        }
+        assert_eq!(state.tick_interval, MIN_NUDGED_TICK);
+    }
+
+    #[test]
+    fn fast_drop_moves_straight_away() {
+        let mut state = state_with(
+            vec![Position((3, 3)), Position((2, 3))],
+            Direction::East,
+            Position((8, 8)),
+        );
+
+        fast_drop(&mut state);
+        update(&mut state, &Config::default(), &[Direction::West], 0.01);
+
+        // The reversal is still refused.
+        assert_eq!(state.snakes[0].parts[0], Position((4, 3)));
+        assert_eq!(state.time_since_last_move, 0.0);
+        update(&mut state, &Config::default(), &[], 0.01);
+        assert_eq!(state.snakes[0].parts[0], Position((4, 3)));
+    }
+
+    #[test]
+    fn long_frames_keep_the_pace() {
+        let config = Config {
+            tick_seconds: 0.25,
+            ..Config::default()
+        };
+        let mut state = state_with(
+            vec![Position((2, 5)), Position((1, 5))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        state.tick_interval = config.tick_seconds;
+
+        // Two and a half moves' worth moves twice, and keeps the half.
+        update(&mut state, &config, &[], 0.625);
+        assert_eq!(state.snakes[0].parts[0], Position((4, 5)));
+        assert_eq!(state.time_since_last_move, 0.125);
+        update(&mut state, &config, &[], 0.125);
+        assert_eq!(state.snakes[0].parts[0], Position((5, 5)));
+    }
+
+    #[test]
+    fn the_autopilot_takes_the_shortest_way_round() {
+        let mut state = state_with(
+            vec![Position((2, 5)), Position((1, 5))],
+            Direction::East,
+            Position((4, 5)),
+        );
+        // A wall straight ahead, which is three cells shorter to go round the top of.
+        state.walls.extend((2..=7).map(|y| Position((3, y))));
+
+        assert_eq!(
+            ai::bfs_direction(&state, &Config::default()),
+            Direction::North
+        );
+        for _ in 0..7 {
+            let direction = ai::bfs_direction(&state, &Config::default());
+            step(&mut state, &[direction]);
+        }
+        assert_eq!(state.snakes[0].parts[0], Position((4, 6)));
+        assert_eq!(state.fruit_eaten, 0);
+        let direction = ai::bfs_direction(&state, &Config::default());
+        step(&mut state, &[direction]);
+        assert_eq!(state.fruit_eaten, 1);
+    }
+
+    #[test]
+    fn the_autopilot_stays_alive_when_it_cant_reach_the_fruit() {
+        let mut state = state_with(
+            vec![Position((2, 5)), Position((1, 5))],
+            Direction::East,
+            Position((8, 8)),
+        );
+        // The fruit is walled in, and so is the way ahead and to the left.
+        state.walls.extend([
+            Position((7, 8)),
+            Position((9, 8)),
+            Position((8, 7)),
+            Position((8, 9)),
+            Position((3, 5)),
+            Position((2, 6)),
+        ]);
+
+        let direction = ai::bfs_direction(&state, &Config::default());
+        assert_eq!(direction, Direction::South);
+        step(&mut state, &[direction]);
+        assert!(!state.is_dead());
+    }
+
+    #[test]
+    fn replays_reproduce_the_game() {
+        let config = Config {
+            layout: Layout::Random,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 42);
+        // Chase the fruit, so that some get eaten and new ones placed.
+        for frame in 0..400 {
+            let turns = if frame % 3 == 0 {
+                vec![ai::bfs_direction(&state, &config)]
+            } else {
+                Vec::new()
+            };
+            let dt = [0.016, 0.017, 0.05][frame % 3];
+            play_frame(
+                &mut state,
+                &config,
+                Frame {
+                    dt,
+                    turns,
+                    ..Frame::default()
+                },
+            );
+        }
+        assert!(state.fruit_eaten > 0);
+
+        let saved = toml::to_string(&Replay::new(&state, &config)).unwrap();
+        let replayed = replay(&toml::from_str(&saved).unwrap());
+
+        assert_eq!(replayed.snakes[0].parts, state.snakes[0].parts);
+        assert_eq!(replayed.fruit, state.fruit);
+        assert_eq!(replayed.score, state.score);
+        assert_eq!(replayed.death_cause, state.death_cause);
+    }
+
+    #[test]
+    fn filling_a_tiny_board_wins_cleanly() {
+        let config = Config {
+            board_width: 3,
+            board_height: 3,
+            wrap_mode: WrapMode::Wrap,
+            lives: 1,
+            ..Config::default()
+        };
+        // A loop through all nine cells, wrapping around, which the snake never catches up on.
+        let around_the_board = |state: &PlayState| match state.snakes[0].parts[0] {
+            Position((1, 1)) | Position((0, 2)) | Position((2, 0)) => Some(Direction::North),
+            _ => Some(Direction::East),
+        };
+
+        let outcome = simulate(&config, around_the_board, 1000);
+
+        assert_eq!(outcome.ending, Ending::ClearedBoard);
+        assert!(outcome.ticks < 1000);
+        assert_eq!(outcome.length, 9);
+    }
+
+    #[test]
+    fn simulated_games_say_how_they_ended() {
+        let config = Config {
+            lives: 1,
+            ..Config::default()
+        };
+        let outcome = simulate(&config, |_| None, 100);
+        assert_eq!(outcome.ending, Ending::Died(DeathCause::Wall));
+
+        let outcome = simulate(&config, |_| None, 3);
+        assert_eq!(outcome.ending, Ending::Unfinished);
+        assert_eq!(outcome.ticks, 3);
+
+        let outcome = simulate(
+            &Config {
+                time_attack: true,
+                time_attack_seconds: 1.0,
+                ..Config::default()
+            },
+            |_| None,
+            1000,
+        );
+        assert_eq!(outcome.ending, Ending::Died(DeathCause::Starved));
+
+        // The autopilot, left to itself, gets somewhere.
+        let outcome = simulate(
+            &Config::default(),
+            |state| Some(ai::bfs_direction(state, &Config::default())),
+            1000,
+        );
+        assert!(outcome.score > 0);
+        assert!(outcome.length > 2);
+    }
+
+    #[test]
+    fn random_layouts_keep_the_board_connected() {
+        for seed in 0..20 {
+            let config = Config {
+                layout: Layout::Random,
+                random_wall_count: 20,
+                ..Config::default()
+            };
+            let state = reset_state(&config, seed);
+
+            assert!(all_open_cells_reachable(
+                &config,
+                &state.walls,
+                state.snakes[0].parts[0]
+            ));
+            assert!(!state
+                .walls
+                .iter()
+                .any(|wall| state.snakes[0].parts.contains(wall)));
+        }
+    }
+
+    #[test]
+    fn hamiltonian_autopilot_fills_the_board() {
+        let config = Config {
+            board_width: 6,
+            board_height: 6,
+            ai_strategy: ai::AiStrategy::Hamiltonian,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 7);
+        toggle_autopilot(&mut state);
+
+        // Following the cycle walks straight through poison, so keep it off the board.
+        for _ in 0..10_000 {
+            state.fruit.retain(|(_, kind)| *kind == FruitKind::Normal);
+            update(&mut state, &config, &[], config.tick_seconds);
+            if state.is_over() {
+                break;
+            }
+        }
+
+        assert!(state.won);
+        assert!(!state.is_dead());
+    }
+
+    #[test]
+    fn hamiltonian_autopilot_leaves_the_cycle_round_whatever_blocks_it() {
+        let config = Config {
+            board_width: 6,
+            board_height: 6,
+            ai_strategy: ai::AiStrategy::Hamiltonian,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 7);
+        // With no fruit to skip ahead to, it takes the next cell along the cycle.
+        state.fruit.clear();
+        let cycle = ai::HamiltonianCycle::new(&config);
+        let head = state.snakes[0].parts[0];
+        let along = ai::hamiltonian_direction(&state, &config, &cycle);
+
+        state.walls.push(next_position(head, along, &config));
+        let around = ai::hamiltonian_direction(&state, &config, &cycle);
+        assert_ne!(around, along);
+        assert!(!state.walls.contains(&next_position(head, around, &config)));
+    }
+
+    #[test]
+    fn hamiltonian_autopilot_steers_round_the_walls_of_later_levels() {
+        let config = Config {
+            board_width: 12,
+            board_height: 12,
+            campaign: true,
+            ai_strategy: ai::AiStrategy::Hamiltonian,
+            ..Config::default()
+        };
+        let mut state = reset_state(&config, 7);
+        next_level(&mut state, &config);
+        state.level_timer = 0.0;
+        toggle_autopilot(&mut state);
+
+        // The level one cycle runs through the pillars of level two and the cross of level three,
+        // so those levels get no cycle and this is all `bfs_direction`. Nothing here dodges a
+        // wall that moves into it, so keep those off the board.
+        while state.level < 4 {
+            state.moving_walls.clear();
+            let dt = state.tick_interval.max(state.level_timer);
+            update(&mut state, &config, &[], dt);
+            assert_eq!(state.lives, config.lives);
+        }
+    }
+
+    #[test]
+    fn hamiltonian_autopilot_steers_round_the_walls_of_a_drawn_level() {
+        let level = parse_level(
+            "########\n#.S....#\n#......#\n#..##..#\n#..##..#\n#.....F#\n#......#\n########\n",
+        )
+        .unwrap();
+        let config = with_level(
+            &Config {
+                ai_strategy: ai::AiStrategy::Hamiltonian,
+                ..Config::default()
+            },
+            level,
+        );
+        let mut state = reset_state(&config, 7);
+        toggle_autopilot(&mut state);
+
+        // Long enough to have gone all the way round a cycle through the walls.
+        for _ in 0..36 {
+            update(&mut state, &config, &[], config.tick_seconds);
+            assert!(!state.is_dead());
+            assert_eq!(state.lives, config.lives);
+        }
+    }
+
+    #[test]
+    fn stick_ignores_drift_and_picks_the_strongest_axis() {
+        assert_eq!(stick_to_direction(0.1, -0.2, 0.5), None);
+        assert_eq!(stick_to_direction(0.9, 0.6, 0.5), Some(Direction::East));
+        assert_eq!(stick_to_direction(-0.3, 0.8, 0.5), Some(Direction::North));
+        assert_eq!(stick_to_direction(0.0, -1.0, 0.5), Some(Direction::South));
+    }
+
+    #[test]
+    fn only_genuine_pairs_are_opposite() {
+        use Direction::*;
+        let all = [North, South, West, East];
+        for one in all {
+            for other in all {
+                let expected = matches!(
+                    (one, other),
+                    (North, South) | (South, North) | (West, East) | (East, West)
+                );
+                assert_eq!(is_opposite_of(one, other), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Controller input. macroquad doesn't see gamepads, so they are read through gilrs.
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use snake::game::{self, Direction};
+
+/// How far the left stick has to be pushed before it counts, so drift doesn't turn the snake.
+const STICK_DEADZONE: f32 = 0.5;
+
+pub struct Gamepads {
+    gilrs: Gilrs,
+    /// Where the left stick was pushed last frame, so that holding it only turns once.
+    stick: Option<Direction>,
+}
+
+impl Gamepads {
+    /// Returns `None`, after saying why, if controllers can't be read on this system.
+    pub fn new() -> Option<Gamepads> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Gamepads { gilrs, stick: None }),
+            Err(err) => {
+                eprintln!("Failed to set up gamepads, playing without them: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Turns asked for on any controller since the last call, from either the D-pad or the
+    /// left stick. This must be called every frame so that old presses don't pile up.
+    pub fn gamepad_to_direction(&mut self) -> Vec<Direction> {
+        let mut turns = Vec::new();
+
+        while let Some(event) = self.gilrs.next_event() {
+            if let EventType::ButtonPressed(button, _) = event.event {
+                let turn = match button {
+                    Button::DPadUp => Some(Direction::North),
+                    Button::DPadDown => Some(Direction::South),
+                    Button::DPadLeft => Some(Direction::West),
+                    Button::DPadRight => Some(Direction::East),
+                    _ => None,
+                };
+                turns.extend(turn);
+            }
+        }
+
+        let stick = self.gilrs.gamepads().find_map(|(_, gamepad)| {
+            game::stick_to_direction(
+                gamepad.value(Axis::LeftStickX),
+                gamepad.value(Axis::LeftStickY),
+                STICK_DEADZONE,
+            )
+        });
+        if stick.is_some() && stick != self.stick {
+            turns.extend(stick);
+        }
+        self.stick = stick;
+
+        turns
+    }
+}
@@ -0,0 +1,52 @@
+//! The best game played, raced against: its snake is replayed alongside the live one, from
+//! the same seed, but only ever drawn. It never blocks or is eaten.
+
+use snake::game::{self, Config, PlayState, Replay};
+
+use crate::replays;
+
+pub struct Ghost {
+    pub state: PlayState,
+    replay: Replay,
+    played: usize,
+}
+
+impl Ghost {
+    /// The best replay, if there is one played on a board like `config`'s. One with a
+    /// different size or edges would wander through walls that aren't there.
+    pub fn load(config: &Config) -> Option<Ghost> {
+        let replay = replays::load_best()?;
+        let fits = replay.config.board_width == config.board_width
+            && replay.config.board_height == config.board_height
+            && replay.config.wrap_mode() == config.wrap_mode()
+            && replay.config.custom_level == config.custom_level;
+        if !fits {
+            return None;
+        }
+        Some(Ghost {
+            state: game::reset_state(&replay.config, replay.seed),
+            replay,
+            played: 0,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    /// Plays the ghost's frames until it's as far into its game as `elapsed`, the live one's.
+    pub fn catch_up(&mut self, elapsed: f32) {
+        while let Some(frame) = self.replay.frames.get(self.played) {
+            if self.state.elapsed + frame.dt > elapsed {
+                break;
+            }
+            game::play_frame(&mut self.state, &self.replay.config, frame.clone());
+            self.played += 1;
+        }
+    }
+
+    /// Whether its game is over, and it should stop being drawn.
+    pub fn is_finished(&self) -> bool {
+        self.played == self.replay.frames.len()
+    }
+}
@@ -0,0 +1,463 @@
+//! Everything the game says to the player, in each of the languages it can be played in. Pick
+//! one with `lang` in `settings.toml`:
+//!
+//! ```toml
+//! lang = "spanish"
+//! ```
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::achievements::Achievement;
+use crate::ai::AiStrategy;
+use crate::game::{DeathCause, Difficulty, Layout, PowerUp, Tile};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    English,
+    Spanish,
+}
+
+/// A piece of text to show. Those with `{}` in them have values filled in by `fill`, in order.
+#[derive(Clone, Copy, Debug)]
+pub enum Text {
+    // The menu, where the `bool`s are whether that option is on.
+    Title,
+    HighScore,
+    Totals,
+    Start,
+    WatchReplay,
+    Mute,
+    Fullscreen,
+    Edit,
+    ChangeTheme,
+    Locked,
+    Achievements,
+    NoAchievements,
+    Grid(bool),
+    Pulse(bool),
+    Gradient(bool),
+    WrapAround(bool),
+    Layout(Layout),
+    Autopilot(AiStrategy),
+    TwoPlayers(bool),
+    Devouring(bool),
+    Survival(bool),
+    Portals(bool),
+    TimeAttack(bool),
+    Rush(bool),
+    Ghost(bool),
+    TouchControls(bool),
+    Campaign(bool),
+    MovingWalls(u32),
+    Difficulty(Difficulty),
+    /// The difficulty a game was played at, on its results.
+    PlayedOn(Difficulty),
+
+    // How to play, worded for whichever keys are bound.
+    Steer,
+    SteerPlayers,
+    Pause,
+    FastDrop,
+    NudgeSpeed,
+    Resume,
+    ZoomAndPan,
+    PauseResume,
+    PauseRestart,
+    PauseQuitToMenu,
+    Restart,
+    Quit,
+    ConfirmQuit,
+
+    // The level editor.
+    EditorTitle,
+    EditorPaint,
+    Brush(Tile),
+    EditorSave,
+    EditorSaved,
+
+    // While playing.
+    /// The number of seconds left before the game starts, with 0 for "GO!".
+    Countdown(u32),
+    AutopilotOn,
+    AutopilotOff,
+    PlayerOne,
+    PlayerTwo,
+    SlowMotion,
+    SpeedBoost,
+    Paused,
+    PausedStalled,
+    Replay,
+    Practice(bool),
+    Hardcore(bool),
+    HardcoreRun,
+    HardcoreHighScore,
+    PlayedHardcore,
+    FruitTrail(bool),
+    PracticeScore,
+    TheEnd,
+    Lives,
+    Rewinds,
+    Rewound,
+    Length,
+    Score,
+    Level,
+    Quota,
+    Speed,
+    Seed,
+
+    // When the game is over.
+    CampaignWon,
+    BoardFull,
+    TargetReached,
+    TimesUp,
+    Died(DeathCause),
+    NewRecord,
+    PlayAgain,
+    Stats,
+    PlayerOneWins,
+    PlayerTwoWins,
+    Draw,
+    // Achievements, as earned and as listed in the menu.
+    Achievement(Achievement),
+    AchievementEarned,
+    PickedUp(PowerUp),
+}
+
+impl Lang {
+    pub fn text(self, text: Text) -> &'static str {
+        match self {
+            Lang::English => english(text),
+            Lang::Spanish => spanish(text),
+        }
+    }
+
+    /// `text`, with its `{}`s filled in with `values`.
+    pub fn fill(self, text: Text, values: &[&dyn Display]) -> String {
+        fill(self.text(text), values)
+    }
+}
+
+/// `template` with each `{}` in it replaced by the next of `values`.
+pub fn fill(template: &str, values: &[&dyn Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut values = values.iter();
+    let mut pieces = template.split("{}");
+    filled.push_str(pieces.next().unwrap_or_default());
+    for piece in pieces {
+        if let Some(value) = values.next() {
+            filled.push_str(&value.to_string());
+        }
+        filled.push_str(piece);
+    }
+    filled
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::Title => "SNAKE",
+        Text::HighScore => "high score of {}",
+        Text::HardcoreHighScore => "hardcore high score of {}",
+        Text::Totals => "all time: {} games, {} won, {} fruit, {}h {}m played, {} turns",
+        Text::Start => "Press 'Enter' to start.",
+        Text::WatchReplay => "Press 'R' to watch the last game again.",
+        Text::Mute => "Press 'M' at any time to mute.",
+        Text::Fullscreen => "Press 'F11' at any time to toggle fullscreen.",
+        Text::Edit => "Press 'E' to draw a level.",
+        Text::ChangeTheme => "Press 'C' at any time to change the colors ({}).",
+        Text::Locked => "{}: reach a score of {} to unlock.",
+        Text::Achievements => "Achievements earned:",
+        Text::NoAchievements => "No achievements yet.",
+        Text::Grid(true) => "Press 'G' at any time to toggle grid lines (on).",
+        Text::Grid(false) => "Press 'G' at any time to toggle grid lines (off).",
+        Text::Pulse(true) => "Press 'F' at any time to toggle pulsing fruit (on).",
+        Text::Pulse(false) => "Press 'F' at any time to toggle pulsing fruit (off).",
+        Text::Gradient(true) => "Press 'B' at any time to toggle the faded tail (on).",
+        Text::Gradient(false) => "Press 'B' at any time to toggle the faded tail (off).",
+        Text::WrapAround(true) => "Press 'T' to toggle wrap-around (on).",
+        Text::WrapAround(false) => "Press 'T' to toggle wrap-around (off).",
+        Text::Layout(Layout::Empty) => "Press 'L' to change the layout (empty).",
+        Text::Layout(Layout::Cross) => "Press 'L' to change the layout (cross).",
+        Text::Layout(Layout::Pillars) => "Press 'L' to change the layout (pillars).",
+        Text::Layout(Layout::Random) => "Press 'L' to change the layout (random).",
+        Text::Autopilot(AiStrategy::Bfs) => "Press 'I' to change the autopilot (shortest path).",
+        Text::Autopilot(AiStrategy::Hamiltonian) => {
+            "Press 'I' to change the autopilot (Hamiltonian cycle)."
+        }
+        Text::TwoPlayers(true) => "Press '2' to toggle two players (on).",
+        Text::TwoPlayers(false) => "Press '2' to toggle two players (off).",
+        Text::Devouring(true) => "Press '3' to toggle devouring, where longer eats shorter (on).",
+        Text::Devouring(false) => "Press '3' to toggle devouring, where longer eats shorter (off).",
+        Text::Survival(true) => "Press 'V' to toggle survival, where the walls close in (on).",
+        Text::Survival(false) => "Press 'V' to toggle survival, where the walls close in (off).",
+        Text::Portals(true) => "Press 'O' to toggle portals (on).",
+        Text::Portals(false) => "Press 'O' to toggle portals (off).",
+        Text::TimeAttack(true) => "Press 'X' to toggle time attack (on).",
+        Text::TimeAttack(false) => "Press 'X' to toggle time attack (off).",
+        Text::Rush(true) => "Press 'U' to toggle rush, where the snake only speeds up (on).",
+        Text::Rush(false) => "Press 'U' to toggle rush, where the snake only speeds up (off).",
+        Text::Practice(true) => "Press 'N' to toggle practice, where nothing is fatal (on).",
+        Text::Practice(false) => "Press 'N' to toggle practice, where nothing is fatal (off).",
+        Text::Hardcore(true) => "Press 'D' to toggle hardcore, with no help at all (on).",
+        Text::Hardcore(false) => "Press 'D' to toggle hardcore, with no help at all (off).",
+        Text::HardcoreRun => "HARDCORE: one life, no shields, no slow motion.",
+        Text::PlayedHardcore => "{}, HARDCORE",
+        Text::FruitTrail(true) => "Press 'A' to toggle the fruit trail (on).",
+        Text::FruitTrail(false) => "Press 'A' to toggle the fruit trail (off).",
+        Text::Ghost(true) => "Press 'J' to toggle racing the ghost of your best game (on).",
+        Text::Ghost(false) => "Press 'J' to toggle racing the ghost of your best game (off).",
+        Text::TouchControls(true) => "Press 'Y' to toggle the on-screen D-pad (on).",
+        Text::TouchControls(false) => "Press 'Y' to toggle the on-screen D-pad (off).",
+        Text::Campaign(true) => "Press 'K' to toggle the campaign (on).",
+        Text::Campaign(false) => "Press 'K' to toggle the campaign (off).",
+        Text::MovingWalls(0) => "Press 'H' to change how many walls move (none).",
+        Text::MovingWalls(1) => "Press 'H' to change how many walls move (one).",
+        Text::MovingWalls(2) => "Press 'H' to change how many walls move (two).",
+        Text::MovingWalls(3) => "Press 'H' to change how many walls move (three).",
+        Text::MovingWalls(_) => "Press 'H' to change how many walls move (lots).",
+        Text::Difficulty(Difficulty::Easy) => {
+            "Press left or right to change the difficulty (easy)."
+        }
+        Text::Difficulty(Difficulty::Normal) => {
+            "Press left or right to change the difficulty (normal)."
+        }
+        Text::Difficulty(Difficulty::Hard) => {
+            "Press left or right to change the difficulty (hard)."
+        }
+        Text::PlayedOn(Difficulty::Easy) => "difficulty: easy",
+        Text::PlayedOn(Difficulty::Normal) => "difficulty: normal",
+        Text::PlayedOn(Difficulty::Hard) => "difficulty: hard",
+
+        Text::Steer => "Use {} or {} to control the snake.",
+        Text::SteerPlayers => "Player one uses {}, player two uses {}.",
+        Text::Pause => "Press {} to pause.",
+        Text::FastDrop => "Press {} to take the next step straight away.",
+        Text::NudgeSpeed => "Press {} or {} to speed up or slow down.",
+        Text::Resume => "Press {} to resume.",
+        Text::ZoomAndPan => {
+            "Scroll to zoom, drag with the middle button or use player two's keys to pan, F5 to follow the head and 'Z' to fit the board."
+        }
+        Text::PauseResume => "Resume",
+        Text::PauseRestart => "Restart",
+        Text::PauseQuitToMenu => "Quit to menu",
+        Text::Restart => "Press {} to return to the menu.",
+        Text::Quit => "Press {} to quit.",
+        Text::ConfirmQuit => "Press {} again to quit, or any other key to stay.",
+
+        Text::EditorTitle => "LEVEL EDITOR",
+        Text::EditorPaint => "Click to paint with the brush, right click to erase.",
+        Text::Brush(Tile::Wall) => "Press 'Tab' to change the brush (wall).",
+        Text::Brush(Tile::Start) => "Press 'Tab' to change the brush (start).",
+        Text::Brush(Tile::Fruit) => "Press 'Tab' to change the brush (fruit).",
+        Text::Brush(Tile::Empty) => "Press 'Tab' to change the brush (eraser).",
+        Text::EditorSave => "Press 'S' to save the level to {}.",
+        Text::EditorSaved => "Saved! Start a game from the menu to play it.",
+
+        Text::Countdown(3..) => "3...",
+        Text::Countdown(2) => "2...",
+        Text::Countdown(1) => "1...",
+        Text::Countdown(0) => "GO!",
+        Text::AutopilotOn => "AUTOPILOT: press {} to take over.",
+        Text::AutopilotOff => "Press {} to let the snake play itself.",
+        Text::PlayerOne => "PLAYER ONE",
+        Text::PlayerTwo => "PLAYER TWO",
+        Text::SlowMotion => "SLOW MOTION",
+        Text::SpeedBoost => "SPEED BOOST",
+        Text::Paused => "PAUSED",
+        Text::PausedStalled => "PAUSED (game stalled)",
+        Text::Replay => "REPLAY",
+        Text::PracticeScore => "PRACTICE: this score doesn't count.",
+        Text::TheEnd => "The end.",
+        Text::Lives => "lives: {}",
+        Text::Length => "length of {}",
+        Text::Score => "score of {}",
+        Text::Level => "level {} of {}",
+        Text::Quota => "{} fruit to go",
+        Text::Speed => "speed of {} moves/s",
+        Text::Seed => "seed {}",
+
+        Text::CampaignWon => "YOU WIN! Every level is cleared.",
+        Text::BoardFull => "YOU WIN! The board is full.",
+        Text::TargetReached => "YOU WIN! The snake is long enough.",
+        Text::TimesUp => "TIME'S UP!",
+        Text::Died(DeathCause::Wall) => "YOU HIT A WALL! R I P",
+        Text::Died(DeathCause::SelfBite) => "YOU BIT YOURSELF! R I P",
+        Text::Died(DeathCause::Poison) => "YOU ATE TOO MUCH POISON! R I P",
+        Text::Died(DeathCause::Starved) => "YOU STARVED! R I P",
+        Text::Died(DeathCause::Eaten) => "YOU WERE EATEN! R I P",
+        Text::NewRecord => "NEW RECORD!",
+        Text::PlayAgain => "Press any other key to play again.",
+        Text::Stats => "ate {} fruit in {}:{}, turning {} times",
+        Text::PlayerOneWins => "PLAYER ONE WINS, {} long to {}.",
+        Text::PlayerTwoWins => "PLAYER TWO WINS, {} long to {}.",
+        Text::Draw => "IT'S A DRAW, both {} long.",
+        Text::Achievement(Achievement::Hungry) => "Hungry (eat 10 fruit in one game)",
+        Text::Achievement(Achievement::Survivor) => "Survivor (last 2 minutes)",
+        Text::Achievement(Achievement::CleanSweep) => "Clean Sweep (win a game)",
+        Text::Achievement(Achievement::ComboMaster) => "Combo Master (build a x5 combo)",
+        Text::AchievementEarned => "Achievement earned: {}",
+        Text::PickedUp(PowerUp::Shield) => "Shield: nothing can hurt you for a while!",
+        Text::PickedUp(PowerUp::SlowMo) => "Slow motion!",
+        Text::PickedUp(PowerUp::Boost) => "Speed boost!",
+        Text::PickedUp(PowerUp::Rewind) => "Rewind: your next crash will be undone!",
+        Text::Rewound => "Rewound!",
+        Text::Rewinds => "rewinds: {}",
+    }
+}
+
+fn spanish(text: Text) -> &'static str {
+    match text {
+        Text::Title => "SERPIENTE",
+        Text::HighScore => "récord de {}",
+        Text::HardcoreHighScore => "récord extremo de {}",
+        Text::Totals => "en total: {} partidas, {} ganadas, {} frutas, {}h {}m jugadas, {} giros",
+        Text::Start => "Pulsa 'Enter' para empezar.",
+        Text::WatchReplay => "Pulsa 'R' para volver a ver la última partida.",
+        Text::Mute => "Pulsa 'M' en cualquier momento para silenciar.",
+        Text::Fullscreen => "Pulsa 'F11' en cualquier momento para la pantalla completa.",
+        Text::Edit => "Pulsa 'E' para dibujar un nivel.",
+        Text::ChangeTheme => "Pulsa 'C' en cualquier momento para cambiar los colores ({}).",
+        Text::Locked => "{}: consigue {} puntos para desbloquearlo.",
+        Text::Achievements => "Logros conseguidos:",
+        Text::NoAchievements => "Todavía no hay logros.",
+        Text::Grid(true) => "Pulsa 'G' en cualquier momento para ver la cuadrícula (sí).",
+        Text::Grid(false) => "Pulsa 'G' en cualquier momento para ver la cuadrícula (no).",
+        Text::Pulse(true) => "Pulsa 'F' en cualquier momento para que la fruta palpite (sí).",
+        Text::Pulse(false) => "Pulsa 'F' en cualquier momento para que la fruta palpite (no).",
+        Text::Gradient(true) => "Pulsa 'B' en cualquier momento para difuminar la cola (sí).",
+        Text::Gradient(false) => "Pulsa 'B' en cualquier momento para difuminar la cola (no).",
+        Text::WrapAround(true) => "Pulsa 'T' para cruzar los bordes (sí).",
+        Text::WrapAround(false) => "Pulsa 'T' para cruzar los bordes (no).",
+        Text::Layout(Layout::Empty) => "Pulsa 'L' para cambiar el tablero (vacío).",
+        Text::Layout(Layout::Cross) => "Pulsa 'L' para cambiar el tablero (cruz).",
+        Text::Layout(Layout::Pillars) => "Pulsa 'L' para cambiar el tablero (pilares).",
+        Text::Layout(Layout::Random) => "Pulsa 'L' para cambiar el tablero (al azar).",
+        Text::Autopilot(AiStrategy::Bfs) => {
+            "Pulsa 'I' para cambiar el piloto automático (camino más corto)."
+        }
+        Text::Autopilot(AiStrategy::Hamiltonian) => {
+            "Pulsa 'I' para cambiar el piloto automático (ciclo hamiltoniano)."
+        }
+        Text::TwoPlayers(true) => "Pulsa '2' para jugar dos (sí).",
+        Text::TwoPlayers(false) => "Pulsa '2' para jugar dos (no).",
+        Text::Devouring(true) => "Pulsa '3' para que la más larga se coma a la otra (sí).",
+        Text::Devouring(false) => "Pulsa '3' para que la más larga se coma a la otra (no).",
+        Text::Survival(true) => "Pulsa 'V' para sobrevivir mientras se cierran las paredes (sí).",
+        Text::Survival(false) => "Pulsa 'V' para sobrevivir mientras se cierran las paredes (no).",
+        Text::Portals(true) => "Pulsa 'O' para poner portales (sí).",
+        Text::Portals(false) => "Pulsa 'O' para poner portales (no).",
+        Text::TimeAttack(true) => "Pulsa 'X' para jugar contrarreloj (sí).",
+        Text::TimeAttack(false) => "Pulsa 'X' para jugar contrarreloj (no).",
+        Text::Rush(true) => "Pulsa 'U' para jugar a toda prisa, cada vez más rápido (sí).",
+        Text::Rush(false) => "Pulsa 'U' para jugar a toda prisa, cada vez más rápido (no).",
+        Text::Practice(true) => "Pulsa 'N' para practicar, sin que nada sea mortal (sí).",
+        Text::Practice(false) => "Pulsa 'N' para practicar, sin que nada sea mortal (no).",
+        Text::Hardcore(true) => "Pulsa 'D' para el modo extremo, sin ninguna ayuda (sí).",
+        Text::Hardcore(false) => "Pulsa 'D' para el modo extremo, sin ninguna ayuda (no).",
+        Text::HardcoreRun => "EXTREMO: una vida, sin escudos ni cámara lenta.",
+        Text::PlayedHardcore => "{}, EXTREMO",
+        Text::FruitTrail(true) => "Pulsa 'A' para el rastro de la fruta (sí).",
+        Text::FruitTrail(false) => "Pulsa 'A' para el rastro de la fruta (no).",
+        Text::Ghost(true) => "Pulsa 'J' para competir con el fantasma de tu mejor partida (sí).",
+        Text::Ghost(false) => "Pulsa 'J' para competir con el fantasma de tu mejor partida (no).",
+        Text::TouchControls(true) => "Pulsa 'Y' para mostrar la cruceta en pantalla (sí).",
+        Text::TouchControls(false) => "Pulsa 'Y' para mostrar la cruceta en pantalla (no).",
+        Text::Campaign(true) => "Pulsa 'K' para jugar la campaña (sí).",
+        Text::Campaign(false) => "Pulsa 'K' para jugar la campaña (no).",
+        Text::MovingWalls(0) => "Pulsa 'H' para cambiar cuántas paredes se mueven (ninguna).",
+        Text::MovingWalls(1) => "Pulsa 'H' para cambiar cuántas paredes se mueven (una).",
+        Text::MovingWalls(2) => "Pulsa 'H' para cambiar cuántas paredes se mueven (dos).",
+        Text::MovingWalls(3) => "Pulsa 'H' para cambiar cuántas paredes se mueven (tres).",
+        Text::MovingWalls(_) => "Pulsa 'H' para cambiar cuántas paredes se mueven (muchas).",
+        Text::Difficulty(Difficulty::Easy) => {
+            "Pulsa izquierda o derecha para cambiar la dificultad (fácil)."
+        }
+        Text::Difficulty(Difficulty::Normal) => {
+            "Pulsa izquierda o derecha para cambiar la dificultad (normal)."
+        }
+        Text::Difficulty(Difficulty::Hard) => {
+            "Pulsa izquierda o derecha para cambiar la dificultad (difícil)."
+        }
+        Text::PlayedOn(Difficulty::Easy) => "dificultad: fácil",
+        Text::PlayedOn(Difficulty::Normal) => "dificultad: normal",
+        Text::PlayedOn(Difficulty::Hard) => "dificultad: difícil",
+
+        Text::Steer => "Usa {} o {} para controlar la serpiente.",
+        Text::SteerPlayers => "El jugador uno usa {}, el jugador dos usa {}.",
+        Text::Pause => "Pulsa {} para pausar.",
+        Text::FastDrop => "Pulsa {} para dar el siguiente paso ya.",
+        Text::NudgeSpeed => "Pulsa {} o {} para ir más rápido o más despacio.",
+        Text::Resume => "Pulsa {} para seguir.",
+        Text::ZoomAndPan => {
+            "Usa la rueda para acercar, arrastra con el botón central o usa las teclas del jugador dos para moverte, F5 para seguir la cabeza y 'Z' para ver todo el tablero."
+        }
+        Text::PauseResume => "Seguir",
+        Text::PauseRestart => "Empezar de nuevo",
+        Text::PauseQuitToMenu => "Volver al menú",
+        Text::Restart => "Pulsa {} para volver al menú.",
+        Text::Quit => "Pulsa {} para salir.",
+        Text::ConfirmQuit => "Pulsa {} otra vez para salir, o cualquier otra tecla para quedarte.",
+
+        Text::EditorTitle => "EDITOR DE NIVELES",
+        Text::EditorPaint => "Haz clic para pintar con el pincel, clic derecho para borrar.",
+        Text::Brush(Tile::Wall) => "Pulsa 'Tab' para cambiar el pincel (pared).",
+        Text::Brush(Tile::Start) => "Pulsa 'Tab' para cambiar el pincel (salida).",
+        Text::Brush(Tile::Fruit) => "Pulsa 'Tab' para cambiar el pincel (fruta).",
+        Text::Brush(Tile::Empty) => "Pulsa 'Tab' para cambiar el pincel (goma).",
+        Text::EditorSave => "Pulsa 'S' para guardar el nivel en {}.",
+        Text::EditorSaved => "¡Guardado! Empieza una partida desde el menú para jugarlo.",
+
+        Text::Countdown(3..) => "3...",
+        Text::Countdown(2) => "2...",
+        Text::Countdown(1) => "1...",
+        Text::Countdown(0) => "¡YA!",
+        Text::AutopilotOn => "PILOTO AUTOMÁTICO: pulsa {} para tomar el control.",
+        Text::AutopilotOff => "Pulsa {} para que la serpiente juegue sola.",
+        Text::PlayerOne => "JUGADOR UNO",
+        Text::PlayerTwo => "JUGADOR DOS",
+        Text::SlowMotion => "CÁMARA LENTA",
+        Text::SpeedBoost => "TURBO",
+        Text::Paused => "EN PAUSA",
+        Text::PausedStalled => "EN PAUSA (juego detenido)",
+        Text::Replay => "REPETICIÓN",
+        Text::PracticeScore => "PRÁCTICA: esta puntuación no cuenta.",
+        Text::TheEnd => "Fin.",
+        Text::Lives => "vidas: {}",
+        Text::Length => "longitud de {}",
+        Text::Score => "puntuación de {}",
+        Text::Level => "nivel {} de {}",
+        Text::Quota => "faltan {} frutas",
+        Text::Speed => "velocidad de {} pasos/s",
+        Text::Seed => "semilla {}",
+
+        Text::CampaignWon => "¡HAS GANADO! Has superado todos los niveles.",
+        Text::BoardFull => "¡HAS GANADO! El tablero está lleno.",
+        Text::TargetReached => "¡HAS GANADO! La serpiente ya es bastante larga.",
+        Text::TimesUp => "¡SE ACABÓ EL TIEMPO!",
+        Text::Died(DeathCause::Wall) => "¡TE HAS CHOCADO CON UNA PARED! D E P",
+        Text::Died(DeathCause::SelfBite) => "¡TE HAS MORDIDO! D E P",
+        Text::Died(DeathCause::Poison) => "¡DEMASIADO VENENO! D E P",
+        Text::Died(DeathCause::Starved) => "¡TE HAS MUERTO DE HAMBRE! D E P",
+        Text::Died(DeathCause::Eaten) => "¡TE HAN COMIDO! D E P",
+        Text::NewRecord => "¡NUEVO RÉCORD!",
+        Text::PlayAgain => "Pulsa cualquier otra tecla para volver a jugar.",
+        Text::Stats => "comiste {} frutas en {}:{}, girando {} veces",
+        Text::PlayerOneWins => "GANA EL JUGADOR UNO, {} de largo contra {}.",
+        Text::PlayerTwoWins => "GANA EL JUGADOR DOS, {} de largo contra {}.",
+        Text::Draw => "EMPATE, los dos de {} de largo.",
+        Text::Achievement(Achievement::Hungry) => "Hambriento (come 10 frutas en una partida)",
+        Text::Achievement(Achievement::Survivor) => "Superviviente (aguanta 2 minutos)",
+        Text::Achievement(Achievement::CleanSweep) => "Limpieza total (gana una partida)",
+        Text::Achievement(Achievement::ComboMaster) => "Maestro del combo (haz un combo x5)",
+        Text::AchievementEarned => "Logro conseguido: {}",
+        Text::PickedUp(PowerUp::Shield) => "Escudo: nada te hará daño por un rato.",
+        Text::PickedUp(PowerUp::SlowMo) => "¡Cámara lenta!",
+        Text::PickedUp(PowerUp::Boost) => "¡Turbo!",
+        Text::PickedUp(PowerUp::Rewind) => "Rebobinar: tu próximo choque se deshará.",
+        Text::Rewound => "¡Rebobinado!",
+        Text::Rewinds => "rebobinados: {}",
+    }
+}
@@ -0,0 +1,27 @@
+//! Boards drawn by hand in a text file, named by `level` in `settings.toml`. See
+//! `game::parse_level` for how they're drawn.
+
+use std::{fs, path::Path};
+
+use snake::game::{self, CustomLevel};
+
+/// Reads the level at `path`, relative to `settings.toml` unless it's absolute. Anything wrong
+/// with it is reported, and the usual board is played instead.
+pub fn load(path: &Path) -> Option<CustomLevel> {
+    let path = crate::data_dir().join(path);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("Failed to read the level {}: {}", path.display(), err);
+            return None;
+        }
+    };
+
+    match game::parse_level(&contents) {
+        Ok(level) => Some(level),
+        Err(err) => {
+            eprintln!("Ignoring the level {}: {}", path.display(), err);
+            None
+        }
+    }
+}
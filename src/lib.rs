@@ -0,0 +1,4 @@
+pub mod achievements;
+pub mod ai;
+pub mod game;
+pub mod lang;
@@ -1,250 +1,1919 @@
-use macroquad::{prelude::*, ui::root_ui};
+mod args;
+mod bindings;
+mod editor;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod ghost;
+mod levels;
+mod replays;
+mod screenshots;
+mod settings;
+mod theme;
+mod touch;
 
-const PART_WIDTH: f32 = 10.0;
-const PART_HEIGHT: f32 = 10.0;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
-#[derive(Clone, Copy, Debug)]
-enum Direction {
-    North,
-    South,
-    West,
-    East,
+use args::Args;
+use bindings::{KeyBindings, KeyHints, KeyPresses};
+use editor::Editor;
+use ghost::Ghost;
+use macroquad::{
+    audio::{
+        load_sound_from_bytes, play_sound, play_sound_once, set_sound_volume, PlaySoundParams,
+        Sound,
+    },
+    prelude::*,
+    ui::root_ui,
+};
+use settings::Settings;
+use snake::achievements::{self, Achievement};
+use snake::game::{
+    self, Config, Direction, Frame, FruitKind, GameEvent, ParticleColor, PlayState, Position,
+    PowerUp, Replay, Tile, WrapMode,
+};
+use snake::lang::{Lang, Text};
+use theme::Themes;
+use touch::{DPad, Swipe};
+
+/// Everything the player can steer the snake with.
+struct Controls {
+    keys: KeyBindings,
+    presses: KeyPresses,
+    hints: KeyHints,
+    dpad: DPad,
+    swipe: Swipe,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<gamepad::Gamepads>,
 }
 
-fn is_opposite_of(one: Direction, other: Direction) -> bool {
-    use Direction::*;
-    matches!(
-        (one, other),
-        (East, West) | (West, East) | (North, South) | (South, North)
-    )
+impl Controls {
+    fn new(lang: Lang, touch_controls: bool, key_repeat: bool) -> Controls {
+        let keys = KeyBindings::load_from_disk();
+        Controls {
+            hints: KeyHints::new(&keys, lang),
+            keys,
+            presses: KeyPresses::new(key_repeat),
+            dpad: DPad::new(touch_controls),
+            swipe: Swipe::default(),
+            #[cfg(feature = "gamepad")]
+            gamepads: gamepad::Gamepads::new(),
+        }
+    }
+
+    /// Turns asked for this frame from the keyboard, the D-pad, swipes and any controllers, in
+    /// order, for player one and then player two. With one player, everything steers player
+    /// one.
+    fn turns(&mut self, two_player: bool) -> [Vec<Direction>; 2] {
+        let pressed = self.presses.take();
+        let mut turns = if two_player {
+            self.keys.turns(&pressed)
+        } else {
+            self.keys.either_turns(&pressed)
+        };
+        turns.extend(self.dpad.turns());
+        turns.extend(self.swipe.turns());
+
+        #[cfg(feature = "gamepad")]
+        if let Some(gamepads) = &mut self.gamepads {
+            turns.extend(gamepads.gamepad_to_direction());
+        }
+
+        if two_player {
+            [turns, self.keys.second_turns(&pressed)]
+        } else {
+            [turns, Vec::new()]
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
-struct Position((f32, f32));
+/// Keys that toggle things whatever the game is doing, so they never count as "any key".
+const ANYTIME_KEYS: [KeyCode; 11] = [
+    KeyCode::M,
+    KeyCode::C,
+    KeyCode::B,
+    KeyCode::G,
+    KeyCode::F,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Z,
+];
+
+/// A frame longer than this means the game stalled, so it pauses itself rather than catch up.
+const STALL_SECONDS: f32 = 0.25;
 
-fn are_basically_eq(this: Position, other: Position) -> bool {
-    let Position((x, y)) = this;
-    let Position((o_x, o_y)) = other;
+/// How long the snake takes to flash and crumble away after dying.
+const DEATH_ANIMATION_SECONDS: f32 = 1.5;
+const DEATH_FLASHES_PER_SECOND: f32 = 8.0;
+/// How often fruit grows and shrinks, and how far it shrinks from filling its cell.
+const FRUIT_PULSES_PER_SECOND: f32 = 1.5;
+const FRUIT_PULSE_DEPTH: f32 = 0.2;
+/// How long fruit takes to grow from nothing after it moves somewhere new.
+const FRUIT_POP_IN_SECONDS: f32 = 0.25;
+/// How opaque the ghost of the best game is, faint enough not to be mistaken for a snake.
+const GHOST_ALPHA: f32 = 0.3;
+/// How opaque a cell of the fruit trail starts out, before it fades.
+const TRAIL_ALPHA: f32 = 0.5;
+/// How long the results have to be up before a key press restarts the game.
+const RESTART_DELAY_SECONDS: f32 = 0.5;
 
-    let dx = x - o_x;
-    let dy = y - o_y;
+/// In time attack, the clock flashes red once there's less than this left on it.
+const CLOCK_WARNING_SECONDS: f32 = 10.0;
+const CLOCK_FLASHES_PER_SECOND: f32 = 4.0;
 
-    dx > -0.1 && dx < 0.1 && dy > -0.1 && dy < 0.1
+/// The most moving walls the menu cycles through. More can be asked for in `settings.toml`.
+const MAX_MOVING_WALLS: u32 = 3;
+
+/// How long the countdown before each game lasts. The last half second of it is "GO!".
+const COUNTDOWN_SECONDS: f32 = 3.5;
+
+enum GameState {
+    Menu,
+    /// Counting down to the start, with the seconds still to go.
+    Starting(PlayState, f32),
+    Playing(PlayState),
+    /// Paused, whether it paused itself because a frame stalled, and which entry of
+    /// `PAUSE_MENU` is picked.
+    Paused(PlayState, bool, usize),
+    GameOver(PlayState),
+    /// Watching a saved game, with how many of its frames have been played so far.
+    Watching(PlayState, Box<Replay>, usize),
+    /// Drawing a level with the mouse.
+    Editing(Editor),
+    /// Making sure the player meant to quit, before going back to where they were.
+    ConfirmQuit(Box<GameState>),
+    Quit,
 }
 
-fn next_position(from: Position, direction: Direction) -> Position {
-    let Position((old_x, old_y)) = from;
+impl GameState {
+    /// The game on the board, if there is one.
+    fn play_state(&self) -> Option<&PlayState> {
+        match self {
+            GameState::Starting(state, _)
+            | GameState::Playing(state)
+            | GameState::Paused(state, ..)
+            | GameState::GameOver(state)
+            | GameState::Watching(state, ..) => Some(state),
+            _ => None,
+        }
+    }
+}
 
-    let (dx, dy) = match direction {
-        Direction::North => (old_x, old_y + 1.0),
-        Direction::South => (old_x, old_y - 1.0),
-        Direction::West => (old_x - 1.0, old_y),
-        Direction::East => (old_x + 1.0, old_y),
-    };
+/// Where the game keeps the files it saves between sessions.
+fn data_dir() -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("snake")
+}
 
-    Position((dx, dy))
+/// The best score ever achieved, and totals across every game played, persisted between
+/// sessions. Games played by the autopilot don't count towards either.
+struct HighScore {
+    best: u32,
+    /// Whether the game just recorded beat the previous best.
+    new_record: bool,
+    /// "high score of N", cached for the same reason as `PlayState::length_label`.
+    label: String,
+    /// The best score in hardcore, which is kept apart from `best`.
+    hardcore_best: u32,
+    hardcore_label: String,
+    games: u32,
+    /// Games won, by filling the board, reaching `target_length` or finishing the campaign.
+    wins: u32,
+    fruit_eaten: u32,
+    seconds: f32,
+    turns: u32,
+    totals_label: String,
+    /// The names of the `theme::UNLOCKS` that `best` has earned.
+    unlocked: Vec<String>,
+    achievements: Vec<Achievement>,
 }
 
-struct PlayState {
-    walls: Vec<Position>,
+impl HighScore {
+    fn path() -> PathBuf {
+        data_dir().join("highscore.txt")
+    }
+
+    /// Reads the high score from disk. A missing or corrupt file is treated as a score of zero
+    /// and no games played.
+    fn load_from_disk(lang: Lang) -> HighScore {
+        let mut high_score = HighScore {
+            best: 0,
+            new_record: false,
+            label: String::new(),
+            hardcore_best: 0,
+            hardcore_label: String::new(),
+            games: 0,
+            wins: 0,
+            fruit_eaten: 0,
+            seconds: 0.0,
+            turns: 0,
+            totals_label: String::new(),
+            unlocked: Vec::new(),
+            achievements: Vec::new(),
+        };
+
+        let contents = fs::read_to_string(Self::path()).unwrap_or_default();
+        // Older versions saved nothing but the score.
+        if let Ok(best) = contents.trim().parse() {
+            high_score.best = best;
+        } else {
+            for (key, value) in contents.lines().filter_map(|line| line.split_once('=')) {
+                let value = value.trim();
+                match key.trim() {
+                    "best" => high_score.best = value.parse().unwrap_or(0),
+                    "hardcore_best" => high_score.hardcore_best = value.parse().unwrap_or(0),
+                    "games" => high_score.games = value.parse().unwrap_or(0),
+                    "wins" => high_score.wins = value.parse().unwrap_or(0),
+                    "fruit_eaten" => high_score.fruit_eaten = value.parse().unwrap_or(0),
+                    "seconds" => high_score.seconds = value.parse().unwrap_or(0.0),
+                    "turns" => high_score.turns = value.parse().unwrap_or(0),
+                    "achievements" => {
+                        high_score.achievements =
+                            value.split(',').filter_map(Achievement::from_key).collect();
+                    }
+                    "unlocked" => {
+                        high_score.unlocked = value
+                            .split(',')
+                            .filter(|name| !name.is_empty())
+                            .map(str::to_string)
+                            .collect();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Scores from before themes were unlocked still count.
+        high_score.unlock_themes();
+        high_score.update_labels(lang);
+        high_score
+    }
+
+    fn save_to_disk(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            path,
+            format!(
+                "best={}\nhardcore_best={}\ngames={}\nwins={}\nfruit_eaten={}\nseconds={}\nturns={}\nunlocked={}\n\
+                 achievements={}\n",
+                self.best,
+                self.hardcore_best,
+                self.games,
+                self.wins,
+                self.fruit_eaten,
+                self.seconds,
+                self.turns,
+                self.unlocked.join(","),
+                self.achievements
+                    .iter()
+                    .map(|achievement| achievement.key())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        )
+    }
+
+    /// Adds a finished game to the totals, and to the high score if it beats it, then saves.
+    /// A `hardcore` game counts towards the hardcore high score instead.
+    fn record(&mut self, state: &PlayState, hardcore: bool, lang: Lang) {
+        let best = if hardcore {
+            &mut self.hardcore_best
+        } else {
+            &mut self.best
+        };
+        self.new_record = state.score > *best;
+        *best = (*best).max(state.score);
+        self.games += 1;
+        if state.won {
+            self.wins += 1;
+        }
+        self.fruit_eaten += state.fruit_eaten;
+        self.seconds += state.elapsed;
+        self.turns += state.turns;
+        self.unlock_themes();
+
+        self.update_labels(lang);
+        if let Err(err) = self.save_to_disk() {
+            eprintln!("Failed to save high score: {}", err);
+        }
+    }
+
+    /// Checks the game being played for achievements, after `events`, saving any that are
+    /// new and announcing them with a toast.
+    fn earn(&mut self, state: &mut PlayState, events: &[GameEvent], lang: Lang) {
+        let earned = achievements::newly_earned(&self.achievements, state, events);
+        if earned.is_empty() {
+            return;
+        }
+        for achievement in earned.iter() {
+            let name = lang.text(Text::Achievement(*achievement));
+            game::push_toast(state, lang.fill(Text::AchievementEarned, &[&name]));
+        }
+        self.achievements.extend(earned);
+        if let Err(err) = self.save_to_disk() {
+            eprintln!("Failed to save high score: {}", err);
+        }
+    }
+
+    fn unlock_themes(&mut self) {
+        for (name, score) in theme::UNLOCKS {
+            let best = self.best.max(self.hardcore_best);
+            if best >= score && !self.unlocked.iter().any(|unlocked| unlocked == name) {
+                self.unlocked.push(name.to_string());
+            }
+        }
+    }
 
-    parts: Vec<Position>,
-    /// The current direction we are headed.
-    direction: Direction,
-    /// The direction we will switch to at next movement.
-    next_direction: Direction,
+    /// The high score that a game played with `config` is up against.
+    fn label(&self, config: &Config) -> &str {
+        if config.hardcore {
+            &self.hardcore_label
+        } else {
+            &self.label
+        }
+    }
+
+    fn update_labels(&mut self, lang: Lang) {
+        self.label = lang.fill(Text::HighScore, &[&self.best]);
+        self.hardcore_label = lang.fill(Text::HardcoreHighScore, &[&self.hardcore_best]);
+        let minutes = self.seconds as u32 / 60;
+        self.totals_label = lang.fill(
+            Text::Totals,
+            &[
+                &self.games,
+                &self.wins,
+                &self.fruit_eaten,
+                &(minutes / 60),
+                &format!("{:02}", minutes % 60),
+                &self.turns,
+            ],
+        );
+    }
+}
 
-    fruit: Position,
+const MUSIC_VOLUME: f32 = 0.5;
 
-    time_since_last_move: f32,
-    dead: bool,
+/// Music and sound effects. Any sound that failed to load is `None` and is simply skipped.
+struct Audio {
+    chomp: Option<Sound>,
+    game_over: Option<Sound>,
+    music: Option<Sound>,
+    /// Silences both the music and the sound effects.
+    muted: bool,
 }
 
-fn random_position_on_board() -> Position {
-    let x = rand::gen_range(1, 9) as f32;
-    let y = rand::gen_range(1, 9) as f32;
+impl Audio {
+    async fn load(muted: bool) -> Audio {
+        Audio {
+            chomp: load_sound("chomp", include_bytes!("../assets/chomp.wav")).await,
+            game_over: load_sound("game over", include_bytes!("../assets/game_over.wav")).await,
+            music: load_sound("music", include_bytes!("../assets/music.wav")).await,
+            muted,
+        }
+    }
+
+    fn start_music(&self) {
+        if let Some(music) = &self.music {
+            play_sound(
+                music,
+                PlaySoundParams {
+                    looped: true,
+                    volume: if self.muted { 0.0 } else { MUSIC_VOLUME },
+                },
+            );
+        }
+    }
 
-    Position((x, y))
+    fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        // The music keeps going underneath so that unmuting picks up where it would have been.
+        if let Some(music) = &self.music {
+            set_sound_volume(music, if self.muted { 0.0 } else { MUSIC_VOLUME });
+        }
+    }
+
+    fn play(&self, sound: &Option<Sound>) {
+        if self.muted {
+            return;
+        }
+        if let Some(sound) = sound {
+            play_sound_once(sound);
+        }
+    }
 }
 
-fn reset_state() -> PlayState {
-    let walls = {
-        let mut walls = Vec::new();
+async fn load_sound(name: &str, bytes: &[u8]) -> Option<Sound> {
+    match load_sound_from_bytes(bytes).await {
+        Ok(sound) => Some(sound),
+        Err(err) => {
+            eprintln!(
+                "Failed to load the {} sound, playing without it: {}",
+                name, err
+            );
+            None
+        }
+    }
+}
 
-        for x in 0..11 {
-            walls.push(Position((x as f32, 0.0)));
-            walls.push(Position((x as f32, 10.0)));
+fn update(
+    game: GameState,
+    config: &mut Config,
+    high_score: &mut HighScore,
+    audio: &mut Audio,
+    controls: &mut Controls,
+    level_path: &Path,
+    ghost: &mut Option<Ghost>,
+) -> GameState {
+    if is_key_pressed(KeyCode::M) {
+        audio.toggle_mute();
+        Settings::save_muted(audio.muted);
+    }
+    // Read every frame, even when not playing, so controller presses don't queue up.
+    let [turns, second_turns] = controls.turns(config.two_player);
+
+    match game {
+        GameState::Menu if bindings::is_pressed(&controls.keys.quit) => {
+            GameState::ConfirmQuit(Box::new(GameState::Menu))
+        }
+        GameState::Paused(state, stalled, picked) if bindings::is_pressed(&controls.keys.quit) => {
+            GameState::ConfirmQuit(Box::new(GameState::Paused(state, stalled, picked)))
         }
-        for y in 1..10 {
-            walls.push(Position((0.0, y as f32)));
-            walls.push(Position((10.0, y as f32)));
+        GameState::Menu => {
+            if is_key_pressed(KeyCode::Y) {
+                controls.dpad.shown = !controls.dpad.shown;
+            }
+            update_menu(config, level_path, ghost)
         }
+        GameState::Starting(mut state, countdown) => {
+            // The snake stays put, but turns still count, so the first move can be picked early.
+            if !turns.is_empty() || !second_turns.is_empty() {
+                let frame = Frame {
+                    dt: 0.0,
+                    turns,
+                    second_turns,
+                    ..Frame::default()
+                };
+                game::play_frame(&mut state, config, frame);
+            }
 
-        walls
-    };
+            let countdown = countdown - get_frame_time();
+            if countdown <= 0.0 {
+                GameState::Playing(state)
+            } else {
+                GameState::Starting(state, countdown)
+            }
+        }
+        GameState::Playing(mut state) => {
+            if bindings::is_pressed(&controls.keys.pause) {
+                return GameState::Paused(state, false, 0);
+            }
+            // Nothing ends a practice game, so it can be left at any time.
+            if config.practice() && bindings::is_pressed(&controls.keys.restart) {
+                return GameState::Menu;
+            }
+            // A frame that took this long, whether from a hitch or a minimized window that
+            // stopped being drawn, is dropped, so there's no burst of movement to come back to.
+            // macroquad doesn't say when the window loses focus, so switching away from a window
+            // that's still drawn isn't caught.
+            if get_frame_time() > STALL_SECONDS {
+                return GameState::Paused(state, true, 0);
+            }
+
+            let frame = Frame {
+                dt: get_frame_time(),
+                turns,
+                second_turns,
+                fast_drop: bindings::is_pressed(&controls.keys.fast_drop),
+                autopilot: bindings::is_pressed(&controls.keys.autopilot),
+                faster: bindings::is_pressed(&controls.keys.faster),
+                slower: bindings::is_pressed(&controls.keys.slower),
+            };
+            let events = game::play_frame(&mut state, config, frame);
+            for event in events.iter() {
+                match event {
+                    GameEvent::Ate => audio.play(&audio.chomp),
+                    GameEvent::Died(_) => audio.play(&audio.game_over),
+                    _ => {}
+                }
+            }
+            // Like the high score, only earned by playing for real.
+            if !state.autopilot_used && !config.practice() {
+                high_score.earn(&mut state, &events, config.lang);
+            }
+            if let Some(ghost) = ghost {
+                ghost.catch_up(state.elapsed);
+            }
+
+            // Leaving `Playing` happens exactly once per game, so this can't replay every frame.
+            if state.is_over() {
+                // Two players share one score, so it can't count as either's best.
+                high_score.new_record = false;
+                if !state.autopilot_used && !config.two_player && !config.practice() {
+                    high_score.record(&state, config.hardcore, config.lang);
+                }
+                let replay = Replay::new(&state, config);
+                if high_score.new_record {
+                    replays::save_best(&replay);
+                }
+                replays::save(&replay);
+                GameState::GameOver(state)
+            } else {
+                GameState::Playing(state)
+            }
+        }
+        GameState::Paused(state, stalled, mut picked) => {
+            let keys = &controls.keys;
+            if bindings::is_pressed(&keys.up) {
+                picked = (picked + PAUSE_MENU.len() - 1) % PAUSE_MENU.len();
+            }
+            if bindings::is_pressed(&keys.down) {
+                picked = (picked + 1) % PAUSE_MENU.len();
+            }
+            // Only a mouse that's moving picks what it's over, so it doesn't fight the keys.
+            let mouse = Vec2::from(mouse_position());
+            let hovered = pause_menu_rows().iter().position(|row| row.contains(mouse));
+            if mouse_delta_position() != Vec2::ZERO {
+                picked = hovered.unwrap_or(picked);
+            }
 
-    PlayState {
-        walls,
-        parts: vec![Position((2.0, 1.0)), Position((1.0, 1.0))],
-        direction: Direction::East,
-        next_direction: Direction::East,
-        time_since_last_move: 0.0,
-        fruit: random_position_on_board(),
-        dead: false,
+            let chosen = if bindings::is_pressed(&keys.pause) {
+                Some(Text::PauseResume)
+            } else if is_key_pressed(KeyCode::Enter) {
+                Some(PAUSE_MENU[picked])
+            } else if is_mouse_button_pressed(MouseButton::Left) {
+                hovered.map(|hovered| PAUSE_MENU[hovered])
+            } else {
+                None
+            };
+            match chosen {
+                // No time passes while paused. Resuming skips this frame's `dt` as well, so
+                // the snake picks up exactly where it left off instead of jumping ahead.
+                Some(Text::PauseResume) => GameState::Playing(state),
+                Some(Text::PauseRestart) => start_game(config, ghost),
+                Some(_) => GameState::Menu,
+                None => GameState::Paused(state, stalled, picked),
+            }
+        }
+        GameState::GameOver(mut state) => {
+            state.death_timer += get_frame_time();
+            game::update_effects(&mut state, get_frame_time());
+            // Only a key pressed once the results have been up a moment counts, so one that
+            // was mashed or held as the snake died doesn't skip straight past them.
+            let restart = state.death_timer >= results_delay(&state) + RESTART_DELAY_SECONDS
+                && get_last_key_pressed().is_some_and(|key| !ANYTIME_KEYS.contains(&key));
+            if bindings::is_pressed(&controls.keys.restart) {
+                GameState::Menu
+            } else if restart {
+                start_game(config, ghost)
+            } else {
+                GameState::GameOver(state)
+            }
+        }
+        GameState::Watching(mut state, replay, played) => {
+            if bindings::is_pressed(&controls.keys.restart) {
+                return GameState::Menu;
+            }
+            // The frames are played back one for one, so it runs at the speed it was played.
+            match replay.frames.get(played) {
+                Some(frame) => {
+                    game::play_frame(&mut state, &replay.config, frame.clone());
+                    GameState::Watching(state, replay, played + 1)
+                }
+                None => {
+                    state.death_timer += get_frame_time();
+                    game::update_effects(&mut state, get_frame_time());
+                    GameState::Watching(state, replay, played)
+                }
+            }
+        }
+        GameState::Editing(mut editor) => {
+            if bindings::is_pressed(&controls.keys.restart) {
+                return GameState::Menu;
+            }
+            if is_key_pressed(KeyCode::Tab) {
+                editor.brush = editor.brush.next();
+            }
+            let tile = if is_mouse_button_down(MouseButton::Left) {
+                Some(editor.brush)
+            } else if is_mouse_button_down(MouseButton::Right) {
+                Some(Tile::Empty)
+            } else {
+                None
+            };
+            // Holding the button down paints every cell dragged over.
+            let cell = BoardLayout::fit_to_screen(config).cell_at(mouse_position().into(), config);
+            if let (Some(tile), Some(cell)) = (tile, cell) {
+                editor.paint(cell, tile);
+            }
+            if is_key_pressed(KeyCode::S) {
+                if let Some(level) = editor.save(config.lang) {
+                    *config = game::with_level(config, level);
+                }
+            }
+            GameState::Editing(editor)
+        }
+        GameState::ConfirmQuit(previous) => {
+            if bindings::is_pressed(&controls.keys.quit) {
+                GameState::Quit
+            } else if get_last_key_pressed().is_some() {
+                *previous
+            } else {
+                GameState::ConfirmQuit(previous)
+            }
+        }
+        GameState::Quit => GameState::Quit,
     }
 }
 
-fn extend_snake_body(state: &mut PlayState) {
-    let Position((x, y)) = match state.parts.last().cloned() {
-        Some(position) => position,
-        None => return,
+fn update_menu(config: &mut Config, level_path: &Path, ghost: &mut Option<Ghost>) -> GameState {
+    // Rush always wraps around.
+    if is_key_pressed(KeyCode::T) && !config.rush {
+        config.wrap_mode = match config.wrap_mode {
+            WrapMode::Walls => WrapMode::Wrap,
+            WrapMode::Wrap => WrapMode::Walls,
+        };
+    }
+
+    if is_key_pressed(KeyCode::L) {
+        config.layout = config.layout.next();
+    }
+
+    if is_key_pressed(KeyCode::I) {
+        config.ai_strategy = config.ai_strategy.next();
+    }
+
+    // A drawn board only has room for one snake, and replaces the campaign's boards. Nor is
+    // there room for two on the smallest boards.
+    if is_key_pressed(KeyCode::Key2)
+        && config.custom_level.is_none()
+        && (config.two_player || game::two_players_fit(config))
+    {
+        config.two_player = !config.two_player;
+    }
+
+    if is_key_pressed(KeyCode::Key3) {
+        config.devouring = !config.devouring;
+    }
+
+    if is_key_pressed(KeyCode::V) {
+        config.shrinking = !config.shrinking;
+    }
+
+    if is_key_pressed(KeyCode::O) {
+        config.portals = !config.portals;
+    }
+
+    if is_key_pressed(KeyCode::X) {
+        config.time_attack = !config.time_attack;
+    }
+
+    if is_key_pressed(KeyCode::U) {
+        config.rush = !config.rush;
+    }
+
+    if is_key_pressed(KeyCode::A) {
+        config.fruit_trail = !config.fruit_trail;
+    }
+
+    if is_key_pressed(KeyCode::N) {
+        config.practice = !config.practice;
+    }
+
+    if is_key_pressed(KeyCode::D) {
+        config.hardcore = !config.hardcore;
+    }
+
+    if is_key_pressed(KeyCode::J) {
+        config.ghost = !config.ghost;
+    }
+
+    if is_key_pressed(KeyCode::K) && config.custom_level.is_none() {
+        config.campaign = !config.campaign;
+    }
+
+    if is_key_pressed(KeyCode::H) {
+        config.moving_walls = (config.moving_walls + 1) % (MAX_MOVING_WALLS + 1);
+    }
+
+    if is_key_pressed(KeyCode::Left) {
+        config.difficulty = config.difficulty.easier();
+    }
+    if is_key_pressed(KeyCode::Right) {
+        config.difficulty = config.difficulty.harder();
+    }
+
+    if is_key_pressed(KeyCode::R) {
+        if let Some(mut replay) = replays::load() {
+            // It's shown in the player's language, whichever it was played in.
+            replay.config.lang = config.lang;
+            let state = game::reset_state(&replay.config, replay.seed);
+            return GameState::Watching(state, Box::new(replay), 0);
+        }
+    }
+
+    if is_key_pressed(KeyCode::E) {
+        return GameState::Editing(Editor::new(config, level_path.to_path_buf()));
+    }
+
+    if is_key_pressed(KeyCode::Enter) {
+        start_game(config, ghost)
+    } else {
+        GameState::Menu
+    }
+}
+
+/// What can be done from the pause menu, in the order it lists them.
+const PAUSE_MENU: [Text; 3] = [Text::PauseResume, Text::PauseRestart, Text::PauseQuitToMenu];
+/// How faint the pause menu's entries are, other than the one picked.
+const UNPICKED_ALPHA: f32 = 0.5;
+
+/// Where each entry of the pause menu is drawn: rows across the whole screen, under the
+/// title, which can be clicked anywhere along.
+fn pause_menu_rows() -> [Rect; PAUSE_MENU.len()] {
+    let unit = screen_width().min(screen_height());
+    let size = unit * 0.06;
+    let top = screen_height() / 2.0 + unit * 0.08;
+    std::array::from_fn(|row| Rect::new(0.0, top + row as f32 * size * 1.5, screen_width(), size))
+}
+
+/// Loads the ghost to race as well, if it's wanted, whose seed the game is then played on.
+fn start_game(config: &Config, ghost: &mut Option<Ghost>) -> GameState {
+    *ghost = config.ghost.then(|| Ghost::load(config)).flatten();
+    let seed = match ghost {
+        Some(ghost) => ghost.seed(),
+        None => config
+            .seed
+            .unwrap_or_else(|| (macroquad::miniquad::date::now() * 1000.0) as u64),
     };
+    println!("Starting a game with seed {}", seed);
+    GameState::Starting(game::reset_state(config, seed), COUNTDOWN_SECONDS)
+}
 
-    let new_part = match state.direction {
-        Direction::North => Position((x, y + 1.0)),
-        Direction::South => Position((x, y - 1.0)),
-        Direction::West => Position((x + 1.0, y)),
-        Direction::East => Position((x - 1.0, y)),
+/// How long after the game ends its results appear: straight away for a win, after the
+/// death animation otherwise.
+fn results_delay(state: &PlayState) -> f32 {
+    if state.is_dead() {
+        DEATH_ANIMATION_SECONDS
+    } else {
+        0.0
+    }
+}
+
+fn render(
+    game: &GameState,
+    config: &Config,
+    themes: &Themes,
+    high_score: &HighScore,
+    controls: &Controls,
+    ghost: Option<&Ghost>,
+    view: &View,
+) {
+    let colors = themes.current();
+    let lang = config.lang;
+    let hints = &controls.hints;
+    match game {
+        GameState::Menu => {
+            render_menu(config, themes, high_score, &controls.dpad);
+            root_ui().label(None, &hints.quit);
+        }
+        GameState::Starting(state, countdown) => {
+            render_board(state, config, themes, ghost, view);
+            root_ui().label(None, steer_hint(config, hints));
+            let seconds = (countdown - 0.5).ceil().max(0.0) as u32;
+            draw_big_centered_text(lang.text(Text::Countdown(seconds)), colors.text);
+            controls.dpad.render(colors.text);
+        }
+        GameState::Playing(state) => {
+            render_board(state, config, themes, ghost, view);
+            root_ui().label(None, steer_hint(config, hints));
+            root_ui().label(None, &hints.pause);
+            root_ui().label(None, &hints.fast_drop);
+            root_ui().label(None, &hints.nudge_speed);
+            if state.autopilot {
+                root_ui().label(None, &hints.autopilot_on);
+            } else {
+                root_ui().label(None, &hints.autopilot_off);
+            }
+            root_ui().label(None, &state.score_label);
+            if state.combo > 1 {
+                root_ui().label(None, &state.combo_label);
+            }
+            if let [one, two] = &state.snakes[..] {
+                root_ui().label(None, lang.text(Text::PlayerOne));
+                root_ui().label(None, &one.length_label);
+                root_ui().label(None, lang.text(Text::PlayerTwo));
+                root_ui().label(None, &two.length_label);
+            } else {
+                root_ui().label(None, &state.lives_label);
+                if state.rewinds > 0 {
+                    root_ui().label(None, &state.rewinds_label);
+                }
+                root_ui().label(None, &state.snakes[0].length_label);
+            }
+            root_ui().label(None, &state.speed_label);
+            if config.campaign {
+                root_ui().label(None, &state.level_label);
+                root_ui().label(None, &state.quota_label);
+            }
+            if state.slow_mo_timer > 0.0 {
+                root_ui().label(None, lang.text(Text::SlowMotion));
+            }
+            if state.boost_timer > 0.0 {
+                root_ui().label(None, lang.text(Text::SpeedBoost));
+                draw_boost_bar(state, colors.boost);
+            }
+            root_ui().label(None, high_score.label(config));
+            if config.practice() {
+                root_ui().label(None, lang.text(Text::PracticeScore));
+                root_ui().label(None, &hints.restart);
+            }
+            if config.hardcore {
+                colored_label(lang.text(Text::HardcoreRun), RED);
+            }
+            if state.level_timer > 0.0 {
+                draw_big_centered_text(&state.level_label, colors.text);
+            }
+            if config.time_attack {
+                draw_clock(state, colors.text);
+            }
+            controls.dpad.render(colors.text);
+        }
+        GameState::Paused(state, stalled, picked) => {
+            render_board(state, config, themes, ghost, view);
+            root_ui().label(None, &hints.resume);
+            root_ui().label(None, &hints.quit);
+            root_ui().label(None, lang.text(Text::ZoomAndPan));
+            let size = screen_width().min(screen_height()) * 0.12;
+            let paused = if *stalled {
+                Text::PausedStalled
+            } else {
+                Text::Paused
+            };
+            draw_centered_text(
+                lang.text(paused),
+                (screen_height() - size) / 2.0,
+                size,
+                colors.text,
+            );
+            for (index, (text, row)) in PAUSE_MENU.iter().zip(pause_menu_rows()).enumerate() {
+                let color = if index == *picked {
+                    colors.text
+                } else {
+                    Color {
+                        a: UNPICKED_ALPHA,
+                        ..colors.text
+                    }
+                };
+                draw_centered_text(lang.text(*text), row.y, row.h, color);
+            }
+        }
+        GameState::GameOver(state) => {
+            render_board(state, config, themes, None, view);
+            if state.death_timer >= results_delay(state) {
+                let title =
+                    if state.won && config.campaign && state.level as usize == game::LEVELS.len() {
+                        lang.text(Text::CampaignWon)
+                    } else if state.won && state.reached_target(config) {
+                        lang.text(Text::TargetReached)
+                    } else if state.won {
+                        lang.text(Text::BoardFull)
+                    } else if state.out_of_time {
+                        lang.text(Text::TimesUp)
+                    } else if config.two_player {
+                        &state.winner_label
+                    } else if let Some(cause) = state.death_cause {
+                        lang.text(Text::Died(cause))
+                    } else {
+                        ""
+                    };
+                let new_record = high_score.new_record.then(|| lang.text(Text::NewRecord));
+                // A practice score is said not to count where the difficulty would be.
+                let difficulty = if config.practice() {
+                    lang.text(Text::PracticeScore).to_string()
+                } else if config.hardcore {
+                    lang.fill(
+                        Text::PlayedHardcore,
+                        &[&lang.text(Text::PlayedOn(config.difficulty))],
+                    )
+                } else {
+                    lang.text(Text::PlayedOn(config.difficulty)).to_string()
+                };
+                draw_results(state, title, new_record, &difficulty, themes);
+                root_ui().label(None, high_score.label(config));
+                root_ui().label(None, &high_score.totals_label);
+                root_ui().label(None, &state.seed_label);
+            }
+            root_ui().label(None, &hints.restart);
+            if state.death_timer >= results_delay(state) {
+                root_ui().label(None, lang.text(Text::PlayAgain));
+            }
+        }
+        GameState::Watching(state, replay, played) => {
+            render_board(state, &replay.config, themes, None, view);
+            root_ui().label(None, lang.text(Text::Replay));
+            root_ui().label(None, &state.score_label);
+            root_ui().label(None, &state.seed_label);
+            if *played == replay.frames.len() {
+                root_ui().label(None, lang.text(Text::TheEnd));
+            }
+            root_ui().label(None, &hints.restart);
+        }
+        GameState::Editing(editor) => {
+            render_editor(editor, config, themes);
+            root_ui().label(None, &hints.restart);
+        }
+        GameState::ConfirmQuit(previous) => {
+            render(previous, config, themes, high_score, controls, ghost, view);
+            root_ui().label(None, &hints.confirm_quit);
+        }
+        GameState::Quit => {}
+    }
+}
+
+fn steer_hint<'a>(config: &Config, hints: &'a KeyHints) -> &'a str {
+    if config.two_player {
+        &hints.steer_players
+    } else {
+        &hints.steer
+    }
+}
+
+fn render_menu(config: &Config, themes: &Themes, high_score: &HighScore, dpad: &DPad) {
+    clear_background(themes.current().background);
+
+    let lang = config.lang;
+    let size = screen_width().min(screen_height()) * 0.12;
+    draw_centered_text(
+        lang.text(Text::Title),
+        size * 0.25,
+        size,
+        themes.current().text,
+    );
+    root_ui().label(None, &high_score.label);
+    for text in [
+        Text::Start,
+        Text::WatchReplay,
+        Text::Edit,
+        Text::Mute,
+        Text::Fullscreen,
+    ] {
+        root_ui().label(None, lang.text(text));
+    }
+    root_ui().label(None, &themes.label);
+    let locked = Color {
+        a: 0.5,
+        ..themes.current().text
     };
+    for label in themes.locked_labels.iter() {
+        colored_label(label, locked);
+    }
+    for text in [
+        Text::Grid(themes.grid),
+        Text::Pulse(themes.pulse),
+        Text::Gradient(themes.gradient),
+        Text::TouchControls(dpad.shown),
+        Text::WrapAround(config.wrap_mode() == WrapMode::Wrap),
+        Text::Layout(config.layout),
+        Text::Autopilot(config.ai_strategy),
+        Text::TwoPlayers(config.two_player),
+        Text::Devouring(config.devouring),
+        Text::Survival(config.shrinking),
+        Text::Portals(config.portals),
+        Text::TimeAttack(config.time_attack),
+        Text::Rush(config.rush),
+        Text::Ghost(config.ghost),
+        Text::Practice(config.practice()),
+        Text::Hardcore(config.hardcore),
+        Text::FruitTrail(config.fruit_trail),
+        Text::Campaign(config.campaign),
+        Text::MovingWalls(config.moving_walls),
+        Text::Difficulty(config.difficulty),
+    ] {
+        root_ui().label(None, lang.text(text));
+    }
+    if high_score.achievements.is_empty() {
+        root_ui().label(None, lang.text(Text::NoAchievements));
+    } else {
+        root_ui().label(None, lang.text(Text::Achievements));
+        for achievement in high_score.achievements.iter() {
+            root_ui().label(None, lang.text(Text::Achievement(*achievement)));
+        }
+    }
+}
 
-    state.parts.push(new_part);
+const MAX_ZOOM: f32 = 4.0;
+/// How much one notch of the mouse wheel zooms in or out.
+const ZOOM_STEP: f32 = 1.25;
+/// How fast the steering keys pan the board while paused, in cells on screen per second.
+const PAN_CELLS_PER_SECOND: f32 = 10.0;
+/// How far in following the head starts, if it isn't zoomed in that far already.
+const FOLLOW_ZOOM: f32 = 2.0;
+/// How quickly the view catches up with the head: the share of the way left that's covered
+/// each second is `1 - e^-FOLLOW_RATE`.
+const FOLLOW_RATE: f32 = 5.0;
+
+/// How far the board is zoomed in, and panned across, for big boards whose cells get small.
+/// The mouse wheel zooms, dragging with the middle button or player two's keys while paused
+/// pans, F5 follows player one's head instead, and 'Z' fits the whole board to the window
+/// again.
+struct View {
+    zoom: f32,
+    /// How many cells the middle of the screen is from the middle of the board. y points up.
+    pan: Vec2,
+    /// Where the mouse was last frame, to drag from.
+    mouse: Vec2,
+    /// Whether the view glides after the head, and stays within the board, rather than
+    /// staying where it's put.
+    follow: bool,
 }
 
-fn update(state: &mut PlayState) {
-    if is_key_pressed(KeyCode::Escape) {
-        *state = reset_state();
+impl View {
+    fn new() -> View {
+        View {
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+            mouse: mouse_position().into(),
+            follow: false,
+        }
     }
 
-    if state.dead {
-        return;
+    /// Switches between following the head, zoomed in, and the whole board.
+    fn toggle_follow(&mut self) {
+        if self.follow {
+            *self = View::new();
+        } else {
+            self.follow = true;
+            self.zoom = self.zoom.max(FOLLOW_ZOOM);
+        }
     }
-    let dt = get_frame_time();
 
-    state.next_direction = input_to_direction(state.direction, KeyCode::Left, Direction::West)
-        .or_else(|| input_to_direction(state.direction, KeyCode::Right, Direction::East))
-        .or_else(|| input_to_direction(state.direction, KeyCode::Up, Direction::North))
-        .or_else(|| input_to_direction(state.direction, KeyCode::Down, Direction::South))
-        .unwrap_or(state.next_direction);
+    /// Moves the view for this frame. `head` is the cell player one's head is in, if there's
+    /// a game on the board to follow.
+    fn update(
+        &mut self,
+        config: &Config,
+        keys: &KeyBindings,
+        paused: bool,
+        head: Option<Position>,
+    ) {
+        let wheel = mouse_wheel().1;
+        if wheel != 0.0 {
+            self.zoom = (self.zoom * ZOOM_STEP.powf(wheel.signum())).clamp(1.0, MAX_ZOOM);
+        }
 
-    state.time_since_last_move += dt;
-    if state.time_since_last_move < 0.2 {
-        return;
+        let mouse = Vec2::from(mouse_position());
+        if is_mouse_button_down(MouseButton::Middle) {
+            let cell_size = BoardLayout::fit_to_screen(config).cell_size * self.zoom;
+            let dragged = (mouse - self.mouse) / cell_size;
+            self.pan -= vec2(dragged.x, -dragged.y);
+        }
+        self.mouse = mouse;
+
+        // Player two's keys only pan while they can't steer. Player one's pick from the pause
+        // menu instead.
+        if paused {
+            let step = PAN_CELLS_PER_SECOND * get_frame_time() / self.zoom;
+            for (held, by) in [
+                (bindings::is_down(&keys.second_up), vec2(0.0, step)),
+                (bindings::is_down(&keys.second_down), vec2(0.0, -step)),
+                (bindings::is_down(&keys.second_left), vec2(-step, 0.0)),
+                (bindings::is_down(&keys.second_right), vec2(step, 0.0)),
+            ] {
+                if held {
+                    self.pan += by;
+                }
+            }
+        }
+
+        if is_key_pressed(KeyCode::Z) {
+            *self = View::new();
+        }
+        if is_key_pressed(KeyCode::F5) {
+            self.toggle_follow();
+        }
+
+        let half_board = vec2(config.board_width as f32, config.board_height as f32) / 2.0;
+        if !self.follow {
+            // The middle of the screen never leaves the board, so some of it is always in view.
+            self.pan = self.pan.clamp(-half_board, half_board);
+            return;
+        }
+        if let Some(Position((x, y))) = head {
+            let target = vec2(x as f32, y as f32) + vec2(0.5, 0.5) - half_board;
+            let share = 1.0 - (-FOLLOW_RATE * get_frame_time()).exp();
+            self.pan = self.pan.lerp(target, share);
+        }
+        // Nothing past the board's edges is shown, unless all of it fits across the screen.
+        let cell_size = BoardLayout::fit_to_screen(config).cell_size * self.zoom;
+        let half_screen = vec2(screen_width(), screen_height()) / cell_size / 2.0;
+        let reach = (half_board - half_screen).max(Vec2::ZERO);
+        self.pan = self.pan.clamp(-reach, reach);
     }
+}
+
+/// Where the board sits on screen. Recomputed every frame so the board stays square and
+/// centered however the window is resized.
+#[derive(Clone, Copy)]
+struct BoardLayout {
+    cell_size: f32,
+    /// The screen position of the grid's (0, 0) corner. Grid y points up (North), so this is
+    /// the bottom-left corner of the board.
+    origin: Vec2,
+}
+
+impl BoardLayout {
+    fn fit_to_screen(config: &Config) -> BoardLayout {
+        let board_width = config.board_width as f32;
+        let board_height = config.board_height as f32;
+        let cell_size = game::cell_size_to_fit(config, screen_width(), screen_height());
 
-    state.time_since_last_move = 0.0;
-    state.direction = state.next_direction;
-    let mut next_position = next_position(state.parts[0], state.direction);
-    for part in state.parts.iter_mut() {
-        std::mem::swap(part, &mut next_position);
+        let origin = vec2(
+            (screen_width() - board_width * cell_size) / 2.0,
+            (screen_height() + board_height * cell_size) / 2.0,
+        );
+
+        BoardLayout { cell_size, origin }
     }
 
-    // Collision check
-    let head = state.parts[0];
-    // Check for collisions with body:
-    if state
-        .parts
-        .iter()
-        .skip(1)
-        .any(|it| are_basically_eq(*it, head))
-    {
-        state.dead = true;
+    /// This layout zoomed in and panned across as `view` says, around the middle of the
+    /// screen.
+    fn with_view(self, view: &View, config: &Config) -> BoardLayout {
+        let cell_size = self.cell_size * view.zoom;
+        // The point on the board that sits in the middle of the screen.
+        let focus = vec2(config.board_width as f32, config.board_height as f32) / 2.0 + view.pan;
+        let center = vec2(screen_width(), screen_height()) / 2.0;
+        BoardLayout {
+            cell_size,
+            origin: center + vec2(-focus.x, focus.y) * cell_size,
+        }
+    }
+
+    /// Converts (fractional) grid coordinates into screen coordinates.
+    fn screen_position(&self, x: f32, y: f32) -> Vec2 {
+        self.origin + vec2(x, -y) * self.cell_size
+    }
+
+    /// The cell under `point` on screen, if it's on the board.
+    fn cell_at(&self, point: Vec2, config: &Config) -> Option<Position> {
+        let Vec2 { x, y } = (point - self.origin) / self.cell_size;
+        // Screen y points down, and grid y up.
+        let (x, y) = (x.floor() as i32, (-y).floor() as i32);
+        let on_board = (0..config.board_width as i32).contains(&x)
+            && (0..config.board_height as i32).contains(&y);
+        on_board.then_some(Position((x, y)))
     }
 
-    // Check for collisions with walls:
-    if state.walls.iter().any(|it| are_basically_eq(*it, head)) {
-        state.dead = true;
+    /// Draws faint lines between every cell.
+    fn draw_grid(&self, config: &Config, color: Color) {
+        let Vec2 { x: left, y: bottom } = self.screen_position(0.0, 0.0);
+        let Vec2 { x: right, y: top } =
+            self.screen_position(config.board_width as f32, config.board_height as f32);
+        for x in 0..=config.board_width {
+            let Vec2 { x, .. } = self.screen_position(x as f32, 0.0);
+            draw_line(x, top, x, bottom, 1.0, color);
+        }
+        for y in 0..=config.board_height {
+            let Vec2 { y, .. } = self.screen_position(0.0, y as f32);
+            draw_line(left, y, right, y, 1.0, color);
+        }
     }
 
-    if are_basically_eq(head, state.fruit) {
-        extend_snake_body(state);
-        state.fruit = random_position_on_board();
+    /// Shades every other cell in `color`, starting from the one after the bottom-left corner.
+    fn draw_checkerboard(&self, config: &Config, color: Color) {
+        for y in 0..config.board_height {
+            for x in (0..config.board_width).filter(|x| (x + y) % 2 == 1) {
+                self.draw_block_at(x as f32, y as f32, color, None);
+            }
+        }
+    }
+
+    /// Draws a block with its bottom-left corner at (fractional) grid coordinates `x`, `y`, so
+    /// that things can be drawn between cells.
+    fn draw_block_at(&self, x: f32, y: f32, color: Color, outline: Option<Color>) {
+        self.draw_scaled_block_at(x, y, 1.0, color, outline);
+    }
+
+    /// Like `draw_block_at`, but `scale` times the size of a cell, kept centered in it.
+    fn draw_scaled_block_at(
+        &self,
+        x: f32,
+        y: f32,
+        scale: f32,
+        color: Color,
+        outline: Option<Color>,
+    ) {
+        let size = self.cell_size * scale;
+        let inset = (1.0 - scale) / 2.0;
+        // The block covers x..x + 1 and y..y + 1, and its top-left corner is where we draw from.
+        let Vec2 { x, y } = self.screen_position(x + inset, y + 1.0 - inset);
+
+        draw_rectangle(x, y, size, size, color);
+
+        if let Some(outline) = outline {
+            draw_rectangle_lines(x, y, size, size, self.cell_size * 0.01, outline);
+        }
     }
 }
 
-fn render(state: &PlayState) {
-    clear_background(GRAY);
+/// `ghost` is drawn faintly under the snakes, until its game is over.
+fn render_board(
+    state: &PlayState,
+    config: &Config,
+    themes: &Themes,
+    ghost: Option<&Ghost>,
+    view: &View,
+) {
+    let colors = themes.current();
+    clear_background(colors.background);
+
+    let mut layout = BoardLayout::fit_to_screen(config).with_view(view, config);
+    if state.shake_magnitude > 0.0 {
+        let shake = vec2(rand::gen_range(-1.0, 1.0), rand::gen_range(-1.0, 1.0));
+        layout.origin += shake * state.shake_magnitude * layout.cell_size;
+    }
+    // Positions live on the integer grid; only convert to screen space when drawing.
+    let draw_block = |Position((x, y)): Position, color: Color| {
+        layout.draw_block_at(x as f32, y as f32, color, themes.outline())
+    };
+
+    if themes.checkerboard {
+        layout.draw_checkerboard(config, colors.checker);
+    }
+    if themes.grid {
+        layout.draw_grid(config, colors.grid);
+    }
+
+    for wall in state.walls.iter().cloned() {
+        draw_block(wall, colors.wall)
+    }
+
+    if let Some((a, b)) = state.portals {
+        draw_block(a, colors.first_portal);
+        draw_block(b, colors.second_portal);
+    }
+
+    for moving_wall in state.moving_walls.iter() {
+        draw_block(moving_wall.position, colors.hazard);
+    }
+
+    // Fading to nothing as they run out.
+    for (Position((x, y)), seconds) in state.trail.iter().cloned() {
+        let color = Color {
+            a: TRAIL_ALPHA * seconds / game::TRAIL_SECONDS,
+            ..colors.fruit
+        };
+        layout.draw_block_at(x as f32, y as f32, color, None);
+    }
+
+    if let Some(ghost) = ghost.filter(|ghost| !ghost.is_finished()) {
+        let color = Color {
+            a: GHOST_ALPHA,
+            ..colors.body
+        };
+        for Position((x, y)) in ghost.state.snakes[0].parts.iter().cloned() {
+            layout.draw_block_at(x as f32, y as f32, color, None);
+        }
+    }
+
+    for (player, snake) in state.snakes.iter().enumerate() {
+        draw_snake(state, config, themes, &layout, player, snake);
+    }
+
+    // Draw Fruit
+    let pulse = if themes.pulse {
+        let phase = (get_time() as f32 * FRUIT_PULSES_PER_SECOND * std::f32::consts::TAU).sin();
+        1.0 - FRUIT_PULSE_DEPTH * (phase + 1.0) / 2.0
+    } else {
+        1.0
+    };
+    let pop_in = state.fruit_spawn_time.map_or(1.0, |spawned| {
+        ((state.elapsed - spawned) / FRUIT_POP_IN_SECONDS).min(1.0)
+    });
+    let fruit_scale = pulse * pop_in;
+    for (Position((x, y)), kind) in state.fruit.iter().cloned() {
+        let color = match kind {
+            FruitKind::Normal => colors.fruit,
+            FruitKind::Poison => colors.poison,
+        };
+        layout.draw_scaled_block_at(x as f32, y as f32, fruit_scale, color, themes.outline());
+        if colors.marks {
+            let center = layout.screen_position(x as f32 + 0.5, y as f32 + 0.5);
+            let radius = layout.cell_size * fruit_scale * 0.2;
+            match kind {
+                FruitKind::Normal => draw_circle(center.x, center.y, radius, colors.outline),
+                FruitKind::Poison => {
+                    draw_circle_lines(center.x, center.y, radius, radius * 0.5, colors.outline)
+                }
+            }
+        }
+    }
+
+    if let Some((golden, lifetime)) = state.golden_fruit {
+        if !blinked_out(lifetime) {
+            draw_block(golden, colors.golden);
+        }
+    }
+
+    if let Some((cell, power_up, lifetime)) = state.power_up {
+        let color = match power_up {
+            PowerUp::Shield => colors.shield,
+            PowerUp::SlowMo => colors.slow_mo,
+            PowerUp::Boost => colors.boost,
+            PowerUp::Rewind => colors.rewind,
+        };
+        if !blinked_out(lifetime) {
+            draw_block(cell, color);
+        }
+    }
 
-    fn draw_block(x: f32, y: f32, color: Color) {
+    for particle in state.particles.iter() {
+        let color = match particle.color {
+            ParticleColor::Fruit => colors.fruit,
+            ParticleColor::Poison => colors.poison,
+            ParticleColor::Golden => colors.golden,
+            ParticleColor::PowerUp(PowerUp::Shield) => colors.shield,
+            ParticleColor::PowerUp(PowerUp::SlowMo) => colors.slow_mo,
+            ParticleColor::PowerUp(PowerUp::Boost) => colors.boost,
+            ParticleColor::PowerUp(PowerUp::Rewind) => colors.rewind,
+        };
+        let size = layout.cell_size * 0.15;
+        let (x, y) = particle.position;
+        let Vec2 { x, y } = layout.screen_position(x, y);
         draw_rectangle(
-            x * PART_WIDTH,
-            y * PART_HEIGHT,
-            PART_WIDTH,
-            PART_HEIGHT,
-            color,
+            x - size / 2.0,
+            y - size / 2.0,
+            size,
+            size,
+            // Fades out as it goes.
+            Color {
+                a: color.a * particle.lifetime / game::PARTICLE_SECONDS,
+                ..color
+            },
         );
+    }
 
-        draw_rectangle_lines(
-            x * PART_WIDTH,
-            y * PART_HEIGHT,
-            PART_WIDTH,
-            PART_HEIGHT,
-            0.1,
-            DARKBROWN,
-        );
+    draw_toasts(state, colors.text);
+}
+
+/// The level being drawn, always with grid lines so the cells are easy to aim at.
+fn render_editor(editor: &Editor, config: &Config, themes: &Themes) {
+    let colors = themes.current();
+    clear_background(colors.background);
+
+    let layout = BoardLayout::fit_to_screen(config);
+    layout.draw_grid(config, colors.grid);
+    for (Position((x, y)), tile) in editor.tiles() {
+        let color = match tile {
+            Tile::Wall => colors.wall,
+            Tile::Start => colors.head,
+            Tile::Fruit => colors.fruit,
+            Tile::Empty => continue,
+        };
+        layout.draw_block_at(x as f32, y as f32, color, themes.outline());
     }
 
-    for Position((x, y)) in state.walls.iter().cloned() {
-        draw_block(x, y, BLACK)
+    let lang = config.lang;
+    root_ui().label(None, lang.text(Text::EditorTitle));
+    root_ui().label(None, lang.text(Text::EditorPaint));
+    root_ui().label(None, lang.text(Text::Brush(editor.brush)));
+    root_ui().label(None, &editor.save_label);
+    root_ui().label(None, &editor.status);
+}
+
+/// Whether something with `seconds_left` should be hidden this frame. It blinks for the last
+/// stretch so the player knows it's about to go.
+fn blinked_out(seconds_left: f32) -> bool {
+    seconds_left < 1.5 && (seconds_left * 6.0) as i32 % 2 == 1
+}
+
+/// Draws `snake`, which belongs to `player`, along with its eyes and any shield around it.
+fn draw_snake(
+    state: &PlayState,
+    config: &Config,
+    themes: &Themes,
+    layout: &BoardLayout,
+    player: usize,
+    snake: &game::Snake,
+) {
+    let colors = themes.current();
+    let draw_block_at =
+        |x: f32, y: f32, color: Color| layout.draw_block_at(x, y, color, themes.outline());
+
+    // A snake that crashed while the other player carries on is just another wall.
+    if snake.dead && !state.is_dead() {
+        for Position((x, y)) in snake.parts.iter().cloned() {
+            draw_block_at(x as f32, y as f32, colors.wall);
+        }
+        return;
     }
 
-    let mut rendered_head = false;
-    for Position((x, y)) in state.parts.iter().cloned() {
-        let color = if rendered_head {
+    // Between moves, each segment slides from the cell it was in towards the cell it is in now.
+    let progress = if state.is_over() || snake.dead {
+        1.0
+    } else {
+        (state.time_since_last_move / game::move_interval(state, config)).min(1.0)
+    };
+    // Once dead, the snake flashes and crumbles away from the tail, leaving just the head.
+    let (segments, flash) = if state.is_dead() {
+        let collapsed = (state.death_timer / DEATH_ANIMATION_SECONDS).min(1.0);
+        let segments = snake.parts.len() - (collapsed * snake.parts.len() as f32) as usize;
+        let flash = if ((state.death_timer * DEATH_FLASHES_PER_SECOND) as u32).is_multiple_of(2) {
+            WHITE
+        } else {
             RED
+        };
+        (segments.max(1), Some(flash))
+    } else if state.respawn_timer > 0.0 {
+        // The same flashing after losing a life, without falling apart.
+        let flash = if ((state.respawn_timer * DEATH_FLASHES_PER_SECOND) as u32).is_multiple_of(2) {
+            WHITE
+        } else {
+            colors.body_color(player, 0, snake.parts.len(), false)
+        };
+        (snake.parts.len(), Some(flash))
+    } else {
+        (snake.parts.len(), None)
+    };
+    let mut head = None;
+    for (index, Position((x, y))) in snake.parts.iter().cloned().enumerate().take(segments) {
+        let (x, y) = match snake.previous_parts.get(index).cloned() {
+            // Freshly grown segments, and ones that just wrapped to the far edge of the board,
+            // have nowhere sensible to slide from.
+            Some(Position((from_x, from_y))) if (x - from_x).abs() + (y - from_y).abs() == 1 => (
+                from_x as f32 + (x - from_x) as f32 * progress,
+                from_y as f32 + (y - from_y) as f32 * progress,
+            ),
+            _ => (x as f32, y as f32),
+        };
+
+        let color = if head.is_some() {
+            colors.body_color(player, index, snake.parts.len(), themes.gradient)
         } else {
-            rendered_head = true;
-            ORANGE
+            head = Some((x, y));
+            colors.head_color(player)
         };
-        draw_block(x, y, color)
+        draw_block_at(x, y, flash.unwrap_or(color));
+    }
+    if let Some((x, y)) = head {
+        if state.shield_timer > 0.0 && !blinked_out(state.shield_timer) {
+            // A glow just outside the head.
+            let glow = layout.cell_size * 0.15;
+            let Vec2 { x, y } = layout.screen_position(x, y + 1.0);
+            draw_rectangle_lines(
+                x - glow,
+                y - glow,
+                layout.cell_size + glow * 2.0,
+                layout.cell_size + glow * 2.0,
+                glow,
+                colors.shield,
+            );
+        }
+        if colors.marks {
+            draw_stripe(layout, x, y, snake.direction, colors.outline);
+        }
+        draw_eyes(layout, x, y, snake.direction);
     }
+}
 
-    {
-        // Draw Fruit
-        let Position((x, y)) = state.fruit;
-        draw_block(x, y, GREEN);
+/// Draws a stripe across the back of the head block at grid coordinates `x`, `y`, so the head
+/// stands out by more than its color.
+fn draw_stripe(layout: &BoardLayout, x: f32, y: f32, direction: Direction, color: Color) {
+    let (width, height) = match direction {
+        Direction::North | Direction::South => (1.0, 0.15),
+        Direction::West | Direction::East => (0.15, 1.0),
+    };
+    let behind = match direction {
+        Direction::North => vec2(0.0, -0.25),
+        Direction::South => vec2(0.0, 0.25),
+        Direction::West => vec2(0.25, 0.0),
+        Direction::East => vec2(-0.25, 0.0),
+    };
+    let center = vec2(x + 0.5, y + 0.5) + behind;
+    let top_left = layout.screen_position(center.x - width / 2.0, center.y + height / 2.0);
+    draw_rectangle(
+        top_left.x,
+        top_left.y,
+        width * layout.cell_size,
+        height * layout.cell_size,
+        color,
+    );
+}
+
+/// Draws a pair of eyes on the head block at grid coordinates `x`, `y`, looking `direction`.
+fn draw_eyes(layout: &BoardLayout, x: f32, y: f32, direction: Direction) {
+    let facing = match direction {
+        Direction::North => vec2(0.0, 1.0),
+        Direction::South => vec2(0.0, -1.0),
+        Direction::West => vec2(-1.0, 0.0),
+        Direction::East => vec2(1.0, 0.0),
+    };
+    let across = vec2(-facing.y, facing.x);
+    let center = vec2(x + 0.5, y + 0.5);
+
+    for side in [-1.0, 1.0] {
+        let eye = center + facing * 0.2 + across * side * 0.22;
+        let pupil = eye + facing * 0.06;
+
+        let eye = layout.screen_position(eye.x, eye.y);
+        draw_circle(eye.x, eye.y, layout.cell_size * 0.15, WHITE);
+        let pupil = layout.screen_position(pupil.x, pupil.y);
+        draw_circle(pupil.x, pupil.y, layout.cell_size * 0.07, BLACK);
     }
+}
+
+/// Draws a bar along the bottom of the screen that shrinks as the speed boost runs out.
+fn draw_boost_bar(state: &PlayState, color: Color) {
+    let height = screen_width().min(screen_height()) * 0.02;
+    let left = state.boost_timer / game::BOOST_SECONDS;
+    draw_rectangle(
+        0.0,
+        screen_height() - height,
+        screen_width() * left,
+        height,
+        color,
+    );
+}
 
-    root_ui().label(None, "Use arrow keys to control the snake.");
-    if state.dead {
-        root_ui().label(None, "YOU DIED. R I P");
-        root_ui().label(None, "Press 'Esc' to restart.");
+/// Draws the time attack clock large across the top of the screen, flashing red when it's
+/// nearly out.
+fn draw_clock(state: &PlayState, color: Color) {
+    let color = if state.time_remaining < CLOCK_WARNING_SECONDS
+        && ((state.time_remaining * CLOCK_FLASHES_PER_SECOND) as u32).is_multiple_of(2)
+    {
+        RED
     } else {
-        // TODO(zac): Allocating every frame!
-        root_ui().label(None, &format!("length of {}", state.parts.len()));
+        color
+    };
+    let size = screen_width().min(screen_height()) * 0.1;
+    draw_centered_text(&state.time_label, 0.0, size, color);
+}
+
+/// The toasts showing, stacked up from the bottom left corner with the newest at the bottom.
+/// Each fades out over its last second.
+fn draw_toasts(state: &PlayState, color: Color) {
+    let size = screen_width().min(screen_height()) * 0.04;
+    for (stacked, toast) in state.toasts.iter().rev().enumerate() {
+        let color = Color {
+            a: toast.seconds.min(1.0),
+            ..color
+        };
+        let y = screen_height() - size * (stacked as f32 * 1.2 + 0.5);
+        draw_text(&toast.text, size * 0.5, y, size, color);
     }
 }
 
-fn input_to_direction(current: Direction, key: KeyCode, mapping: Direction) -> Option<Direction> {
-    if is_key_pressed(key) && !is_opposite_of(mapping, current) {
-        Some(mapping)
-    } else {
-        None
+/// The final score and how the game went, large in the middle of the screen over a faded board.
+/// `new_record` is what to say if the score beat the high score.
+fn draw_results(
+    state: &PlayState,
+    title: &str,
+    new_record: Option<&str>,
+    difficulty: &str,
+    themes: &Themes,
+) {
+    let colors = themes.current();
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color {
+            a: 0.6,
+            ..colors.background
+        },
+    );
+
+    let unit = screen_width().min(screen_height());
+    let mut lines = vec![(title, unit * 0.08, colors.text)];
+    if let Some(new_record) = new_record {
+        let flash =
+            if ((state.death_timer * DEATH_FLASHES_PER_SECOND / 2.0) as u32).is_multiple_of(2) {
+                colors.golden
+            } else {
+                colors.text
+            };
+        lines.push((new_record, unit * 0.08, flash));
+    }
+    lines.push((&state.score_label, unit * 0.06, colors.text));
+    lines.push((&state.snakes[0].length_label, unit * 0.05, colors.text));
+    lines.push((&state.stats_label, unit * 0.04, colors.text));
+    lines.push((difficulty, unit * 0.04, colors.text));
+
+    let gap = unit * 0.02;
+    let height: f32 = lines.iter().map(|(_, size, _)| size + gap).sum();
+    let mut y = (screen_height() - height) / 2.0;
+    for (text, size, color) in lines {
+        draw_centered_text(text, y, size, color);
+        y += size + gap;
     }
 }
 
-#[macroquad::main("Snake")]
-async fn main() {
-    let mut state = reset_state();
+/// Draws `text` across the middle of the screen, sized to fit the window.
+fn draw_big_centered_text(text: &str, color: Color) {
+    let size = screen_width().min(screen_height()) * 0.25;
+    draw_centered_text(text, (screen_height() - size) / 2.0, size, color);
+}
 
-    {
-        let label_style = root_ui().style_builder().text_color(WHITE).build();
-        let skin = macroquad::ui::Skin {
-            label_style,
-            ..root_ui().default_skin()
+/// Draws `text` centered across the screen with its top at `y`. It's `size` high, unless it
+/// has to shrink to fit across the window.
+fn draw_centered_text(text: &str, y: f32, size: f32, color: Color) {
+    let mut font_size = size as u16;
+    let mut measured = measure_text(text, None, font_size, 1.0);
+    if measured.width > screen_width() * 0.9 {
+        font_size = (font_size as f32 * screen_width() * 0.9 / measured.width) as u16;
+        measured = measure_text(text, None, font_size, 1.0);
+    }
+    draw_text(
+        text,
+        (screen_width() - measured.width) / 2.0,
+        y + measured.offset_y,
+        font_size as f32,
+        color,
+    );
+}
+
+/// How often the FPS overlay's numbers change, so they can be read and don't need re-formatting
+/// every frame.
+const FPS_REFRESH_SECONDS: f32 = 0.25;
+
+/// The frame rate, shown small in the top right corner when toggled on with F3.
+struct FpsOverlay {
+    shown: bool,
+    label: String,
+    since_refresh: f32,
+}
+
+impl FpsOverlay {
+    fn new() -> FpsOverlay {
+        FpsOverlay {
+            shown: false,
+            label: String::new(),
+            since_refresh: FPS_REFRESH_SECONDS,
+        }
+    }
+
+    fn update(&mut self) {
+        if is_key_pressed(KeyCode::F3) {
+            self.shown = !self.shown;
+        }
+        if !self.shown {
+            return;
+        }
+
+        self.since_refresh += get_frame_time();
+        if self.since_refresh >= FPS_REFRESH_SECONDS {
+            self.since_refresh = 0.0;
+            self.label = format!("{} fps, {:.1} ms", get_fps(), get_frame_time() * 1000.0);
+        }
+    }
+
+    fn render(&self, color: Color) {
+        if !self.shown {
+            return;
+        }
+        let font_size = 16;
+        let size = measure_text(&self.label, None, font_size, 1.0);
+        draw_text(
+            &self.label,
+            screen_width() - size.width - 4.0,
+            size.offset_y + 4.0,
+            font_size as f32,
+            color,
+        );
+    }
+}
+
+/// What the game is doing inside, for working on it: shown small under the frame rate when
+/// toggled on with F4, which only debug builds listen for.
+struct DebugOverlay {
+    shown: bool,
+    lines: Vec<String>,
+    since_refresh: f32,
+}
+
+impl DebugOverlay {
+    fn new() -> DebugOverlay {
+        DebugOverlay {
+            shown: false,
+            lines: Vec::new(),
+            since_refresh: FPS_REFRESH_SECONDS,
+        }
+    }
+
+    fn update(&mut self, game: &GameState, config: &Config) {
+        if cfg!(debug_assertions) && is_key_pressed(KeyCode::F4) {
+            self.shown = !self.shown;
+        }
+        if !self.shown {
+            return;
+        }
+
+        self.since_refresh += get_frame_time();
+        if self.since_refresh < FPS_REFRESH_SECONDS {
+            return;
+        }
+        self.since_refresh = 0.0;
+        let Some(state) = game.play_state() else {
+            self.lines.clear();
+            return;
         };
-        root_ui().push_skin(&skin);
+        let mut lines = vec![
+            format!("{} free cells", state.free_cells(config).len()),
+            format!("tick {:.3} s", state.tick_interval),
+            format!("fruit picked from {} cells", state.fruit_choices),
+        ];
+        for (player, snake) in state.snakes.iter().enumerate() {
+            lines.push(format!(
+                "player {} turns {:?}",
+                player + 1,
+                snake.pending_turns
+            ));
+        }
+        self.lines = lines;
+    }
+
+    fn render(&self, color: Color) {
+        if !self.shown {
+            return;
+        }
+        let font_size = 16;
+        // Starting below the frame rate's line.
+        let mut y = 4.0 + font_size as f32;
+        for line in &self.lines {
+            let size = measure_text(line, None, font_size, 1.0);
+            y += size.height + 4.0;
+            draw_text(
+                line,
+                screen_width() - size.width - 4.0,
+                y,
+                font_size as f32,
+                color,
+            );
+        }
+    }
+}
+
+/// Draws a single label in `color`, leaving the rest as they were.
+fn colored_label(text: &str, color: Color) {
+    let label_style = root_ui().style_builder().text_color(color).build();
+    let skin = macroquad::ui::Skin {
+        label_style,
+        ..root_ui().default_skin()
+    };
+    root_ui().push_skin(&skin);
+    root_ui().label(None, text);
+    root_ui().pop_skin();
+}
+
+/// Restyles the labels drawn by `root_ui` in `color`.
+fn use_text_color(color: Color) {
+    let label_style = root_ui().style_builder().text_color(color).build();
+    let skin = macroquad::ui::Skin {
+        label_style,
+        ..root_ui().default_skin()
+    };
+    root_ui().pop_skin();
+    root_ui().push_skin(&skin);
+}
+
+fn main() {
+    // Read before the window opens, so a mistake on the command line doesn't flash one up.
+    let args = Args::parse_or_exit();
+    macroquad::Window::new("Snake", run(args));
+}
+
+async fn run(args: Args) {
+    let Settings {
+        game: mut config,
+        colors,
+        theme,
+        solid_blocks,
+        checkerboard,
+        mut fullscreen,
+        muted,
+        level,
+        touch_controls,
+        follow_head,
+        key_repeat,
+    } = Settings::load_from_disk();
+    args.apply(&mut config);
+    // A board made smaller from the command line may not have room for two players.
+    config.two_player &= game::two_players_fit(&config);
+    if let Some(level) = level.as_deref().and_then(levels::load) {
+        config = game::with_level(&config, level);
+    }
+    // The editor saves over the level being played, if there is one.
+    let level_path = level.unwrap_or_else(|| PathBuf::from("level.txt"));
+    if fullscreen {
+        set_fullscreen(true);
     }
+    if let Some(seed) = std::env::var("SNAKE_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+    {
+        config.seed = Some(seed);
+    }
+    let mut game = GameState::Menu;
+    let mut high_score = HighScore::load_from_disk(config.lang);
+    let mut audio = Audio::load(muted).await;
+    let mut controls = Controls::new(
+        config.lang,
+        touch_controls.unwrap_or(touch::SHOWN_BY_DEFAULT),
+        key_repeat,
+    );
+    audio.start_music();
 
-    set_camera(&Camera2D::from_display_rect(Rect {
-        x: 0.0,
-        y: 0.0,
-        w: 11.0 * PART_WIDTH,
-        h: 11.0 * PART_HEIGHT,
-    }));
+    let mut themes = Themes::new(colors, config.lang, &high_score.unlocked);
+    themes.outlines = !solid_blocks;
+    themes.checkerboard = checkerboard;
+    if let Some(theme) = theme {
+        if !themes.select(&theme) {
+            eprintln!(
+                "There is no unlocked theme called {:?}, using {}.",
+                theme,
+                themes.current().name
+            );
+        }
+    }
+    use_text_color(themes.current().text);
 
+    let mut fps = FpsOverlay::new();
+    let mut debug = DebugOverlay::new();
+    let mut ghost = None;
+    let mut view = View::new();
+    if follow_head {
+        view.toggle_follow();
+    }
     loop {
-        update(&mut state);
-        render(&state);
+        game = update(
+            game,
+            &mut config,
+            &mut high_score,
+            &mut audio,
+            &mut controls,
+            &level_path,
+            &mut ghost,
+        );
+        if let GameState::Quit = game {
+            break;
+        }
+        themes.unlock(&high_score.unlocked);
+        view.update(
+            &config,
+            &controls.keys,
+            matches!(game, GameState::Paused(..)),
+            game.play_state().map(|state| state.snakes[0].parts[0]),
+        );
+        if is_key_pressed(KeyCode::C) {
+            themes.next();
+            use_text_color(themes.current().text);
+        }
+        if is_key_pressed(KeyCode::B) {
+            themes.toggle_gradient();
+        }
+        if is_key_pressed(KeyCode::G) {
+            themes.toggle_grid();
+        }
+        if is_key_pressed(KeyCode::F) {
+            themes.toggle_pulse();
+        }
+        // The board is fitted to the window every frame, so it recenters by itself.
+        if is_key_pressed(KeyCode::F11) {
+            fullscreen = !fullscreen;
+            set_fullscreen(fullscreen);
+            Settings::save_fullscreen(fullscreen);
+        }
+        fps.update();
+        debug.update(&game, &config);
+        render(
+            &game,
+            &config,
+            &themes,
+            &high_score,
+            &controls,
+            ghost.as_ref(),
+            &view,
+        );
+        fps.render(themes.current().text);
+        debug.render(themes.current().text);
+        // Taken of the frame just drawn, so it matches what's on screen.
+        if is_key_pressed(KeyCode::F12) {
+            screenshots::save();
+        }
         next_frame().await
     }
 }
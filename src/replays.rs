@@ -0,0 +1,68 @@
+//! The last game played, saved as `replay.toml` next to the high score file so it can be
+//! watched again from the menu, and the best one, saved as `best.toml` to race as a ghost.
+
+use std::{fs, io, path::PathBuf};
+
+use snake::game::Replay;
+
+fn path(name: &str) -> PathBuf {
+    crate::data_dir().join(name)
+}
+
+/// Saves `replay` over the last one. A failure is reported, but doesn't stop the game.
+pub fn save(replay: &Replay) {
+    write(path("replay.toml"), replay);
+}
+
+/// Saves `replay` over the best one, for a game that set a new high score.
+pub fn save_best(replay: &Replay) {
+    write(path("best.toml"), replay);
+}
+
+fn write(path: PathBuf, replay: &Replay) {
+    let result = toml::to_string(replay)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+        .and_then(|contents| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, contents)
+        });
+    if let Err(err) = result {
+        eprintln!("Failed to save the replay: {}", err);
+    }
+}
+
+/// Reads the last saved replay, if there is one that can be understood.
+pub fn load() -> Option<Replay> {
+    let path = path("replay.toml");
+    if !path.exists() {
+        eprintln!("There is no replay to watch yet.");
+        return None;
+    }
+    read(path)
+}
+
+/// Reads the best saved replay. Not having one yet is normal, so isn't reported.
+pub fn load_best() -> Option<Replay> {
+    read(path("best.toml"))
+}
+
+fn read(path: PathBuf) -> Option<Replay> {
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return None;
+        }
+    };
+
+    match toml::from_str(&contents) {
+        Ok(replay) => Some(replay),
+        Err(err) => {
+            eprintln!("Ignoring {}, it is malformed: {}", path.display(), err);
+            None
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Screenshots, saved as PNGs in a `screenshots` folder next to the high score file.
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use macroquad::prelude::*;
+
+/// Saves what's on screen right now. Call it after drawing, before `next_frame`. A failure is
+/// reported, but doesn't stop the game.
+pub fn save() {
+    let screen = get_screen_data();
+    let (width, height) = (screen.width as usize, screen.height as usize);
+    // The screen is read bottom row first.
+    let rows: Vec<u8> = screen
+        .bytes
+        .chunks_exact(width * 4)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+
+    let dir = crate::data_dir().join("screenshots");
+    let path = dir.join(file_name());
+    let result = fs::create_dir_all(&dir)
+        .map_err(image::ImageError::IoError)
+        .and_then(|()| {
+            image::save_buffer(
+                &path,
+                &rows,
+                width as u32,
+                height as u32,
+                image::ColorType::Rgba8,
+            )
+        });
+    match result {
+        Ok(()) => println!("Saved a screenshot to {}", path.display()),
+        Err(err) => eprintln!("Failed to save a screenshot: {}", err),
+    }
+}
+
+/// "snake-2024-05-17-13-04-59.png", for when it was taken, in UTC.
+fn file_name() -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    let time = seconds % 86400;
+    PathBuf::from(format!(
+        "snake-{:04}-{:02}-{:02}-{:02}-{:02}-{:02}.png",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    ))
+}
+
+/// The year, month and day `days` after 1970-01-01, by Howard Hinnant's algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,176 @@
+//! Settings read from `settings.toml` at startup, next to the high score file, so the game can
+//! be tweaked without recompiling. Anything left out keeps its default:
+//!
+//! ```toml
+//! board_width = 15
+//! board_height = 11
+//! tick_seconds = 0.15
+//! wrap_mode = "wrap"
+//!
+//! [colors]
+//! head = [255, 200, 0]
+//! background = [40, 40, 40]
+//! ```
+
+use std::{fs, io, path::PathBuf};
+
+use serde::Deserialize;
+use snake::game::{self, Config};
+
+use crate::theme::Theme;
+
+/// Boards smaller than this don't leave room for the snake and a fruit inside the walls.
+pub const MIN_BOARD_WIDTH: u32 = 5;
+pub const MIN_BOARD_HEIGHT: u32 = 3;
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    #[serde(flatten)]
+    pub game: Config,
+    /// Added to the built-in themes, and used from the start.
+    pub colors: Option<Theme>,
+    /// The name of the theme to start with, in place of the first one.
+    pub theme: Option<String>,
+    /// Draws every block as a solid cell, without the thin outline around it.
+    pub solid_blocks: bool,
+    /// Shades every other cell of the board, so they're easier to count.
+    pub checkerboard: bool,
+    /// Starts the game fullscreen. Toggling fullscreen saves the choice back here.
+    pub fullscreen: bool,
+    /// Starts with the music and sound effects silenced. Toggling mute saves the choice back
+    /// here.
+    pub muted: bool,
+    /// A text file with a board drawn in it, to play on instead of the usual one.
+    pub level: Option<PathBuf>,
+    /// Shows the on-screen D-pad. Left out, it's shown on phones and the web but not desktops.
+    pub touch_controls: Option<bool>,
+    /// Starts zoomed in on the snake's head, following it around the board.
+    pub follow_head: bool,
+    /// Counts the repeats the keyboard sends while a steering key is held as more turns.
+    pub key_repeat: bool,
+}
+
+impl Settings {
+    fn path() -> PathBuf {
+        crate::data_dir().join("settings.toml")
+    }
+
+    /// Reads `settings.toml`. If it is missing, the defaults are used. If it can't be
+    /// understood, that is reported and the defaults are used instead.
+    pub fn load_from_disk() -> Settings {
+        let path = Self::path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Settings::default(),
+            Err(err) => {
+                eprintln!("Failed to read {}, using defaults: {}", path.display(), err);
+                return Settings::default();
+            }
+        };
+
+        match toml::from_str::<Settings>(&contents).map_err(|err| err.to_string()) {
+            Ok(settings) => match settings.check() {
+                Ok(()) => settings,
+                Err(err) => {
+                    eprintln!("Ignoring {}: {}", path.display(), err);
+                    Settings::default()
+                }
+            },
+            Err(err) => {
+                eprintln!("Ignoring {}, it is malformed: {}", path.display(), err);
+                Settings::default()
+            }
+        }
+    }
+
+    /// Remembers `fullscreen` in `settings.toml`, if there is one.
+    pub fn save_fullscreen(fullscreen: bool) {
+        Self::save("fullscreen", fullscreen);
+    }
+
+    /// Remembers `muted` in `settings.toml`, if there is one.
+    pub fn save_muted(muted: bool) {
+        Self::save("muted", muted);
+    }
+
+    /// Sets `name` to `value` in `settings.toml`, if there is one, leaving the rest of the file
+    /// as it was written.
+    fn save(name: &str, value: bool) {
+        let path = Self::path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return;
+        };
+
+        let setting = format!("{} = {}", name, value);
+        let mut lines: Vec<&str> = contents.lines().collect();
+        // Top level settings have to come before the first table, such as `[colors]`.
+        let top_level = lines
+            .iter()
+            .position(|line| line.trim_start().starts_with('['))
+            .unwrap_or(lines.len());
+        let existing = lines[..top_level]
+            .iter()
+            .position(|line| line.split('=').next().map(str::trim) == Some(name));
+        match existing {
+            Some(index) => lines[index] = &setting,
+            None => lines.insert(0, &setting),
+        }
+
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        if let Err(err) = fs::write(&path, contents) {
+            eprintln!("Failed to save to {}: {}", path.display(), err);
+        }
+    }
+
+    /// Catches settings that parse but that the game can't be played with.
+    fn check(&self) -> Result<(), String> {
+        if self.game.board_width < MIN_BOARD_WIDTH || self.game.board_height < MIN_BOARD_HEIGHT {
+            return Err(format!(
+                "the board must be at least {} by {}",
+                MIN_BOARD_WIDTH, MIN_BOARD_HEIGHT
+            ));
+        }
+        if self.game.two_player && !game::two_players_fit(&self.game) {
+            return Err("the board is too small for two players".to_string());
+        }
+        if self.game.lives == 0 {
+            return Err("lives must be at least 1".to_string());
+        }
+        if self.game.slow_mo_factor <= 0.0 || !self.game.slow_mo_factor.is_finite() {
+            return Err("slow_mo_factor must be more than zero".to_string());
+        }
+        if self.game.tick_seconds <= 0.0 || !self.game.tick_seconds.is_finite() {
+            return Err("tick_seconds must be more than zero".to_string());
+        }
+        if self.game.time_attack_seconds <= 0.0 || !self.game.time_attack_seconds.is_finite() {
+            return Err("time_attack_seconds must be more than zero".to_string());
+        }
+        if self
+            .game
+            .target_length
+            .is_some_and(|target| target <= self.game.start_length)
+        {
+            return Err("target_length must be longer than start_length".to_string());
+        }
+        if self.game.shrink_seconds <= 0.0 || !self.game.shrink_seconds.is_finite() {
+            return Err("shrink_seconds must be more than zero".to_string());
+        }
+        if !(1..=game::MAX_REWIND_TICKS).contains(&self.game.rewind_ticks) {
+            return Err(format!(
+                "rewind_ticks must be from 1 to {}",
+                game::MAX_REWIND_TICKS
+            ));
+        }
+        // Weights can't be negative, or they wouldn't have parsed.
+        let values = &self.game.fruit_values;
+        if [values.normal, values.poison, values.golden]
+            .iter()
+            .all(|value| value.weight == 0)
+        {
+            return Err("the fruit_values weights can't all be zero".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,495 @@
+//! The colors everything is drawn in, and the built-in sets of them to pick from.
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Deserializer};
+use snake::lang::{Lang, Text};
+
+/// How opaque the colors drawn over other things are.
+const FAINT: f32 = 0.25;
+
+/// The built-in themes that are earned, and the high score that unlocks each. The rest are
+/// there from the start.
+pub const UNLOCKS: [(&str, u32); 3] = [("dark", 20), ("light", 50), ("retro", 100)];
+
+/// In `settings.toml` each color is an `[r, g, b]` of 0 to 255.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Shown in the menu. A theme from `settings.toml` is always called "custom".
+    #[serde(skip, default = "custom_name")]
+    pub name: &'static str,
+    #[serde(deserialize_with = "rgb")]
+    pub background: Color,
+    /// Every other cell, when the board is drawn as a checkerboard.
+    #[serde(deserialize_with = "rgb")]
+    pub checker: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub wall: Color,
+    /// Walls that move.
+    #[serde(deserialize_with = "rgb")]
+    pub hazard: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub head: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub body: Color,
+    /// What the body fades into by the end of the tail, when the gradient is on.
+    #[serde(deserialize_with = "rgb")]
+    pub tail: Color,
+    /// Player two's snake, in a two player game.
+    #[serde(deserialize_with = "rgb")]
+    pub second_head: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub second_body: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub second_tail: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub fruit: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub poison: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub golden: Color,
+    /// The shield power-up, and the glow around a shielded head.
+    #[serde(deserialize_with = "rgb")]
+    pub shield: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub slow_mo: Color,
+    /// The speed boost power-up, and the bar showing how long it has left.
+    #[serde(deserialize_with = "rgb")]
+    pub boost: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub rewind: Color,
+    /// The two ends of a pair of portals.
+    #[serde(deserialize_with = "rgb")]
+    pub first_portal: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub second_portal: Color,
+    /// The faint lines between cells, when they're turned on.
+    #[serde(deserialize_with = "rgb_faint")]
+    pub grid: Color,
+    /// The thin line drawn around every block.
+    #[serde(deserialize_with = "rgb")]
+    pub outline: Color,
+    #[serde(deserialize_with = "rgb")]
+    pub text: Color,
+    /// Marks things with shapes as well as color: a stripe across each head, a dot on fruit and
+    /// a ring on poison.
+    pub marks: bool,
+}
+
+/// The game's original colors.
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            name: "classic",
+            background: GRAY,
+            checker: Color::from_rgba(120, 120, 120, 255),
+            wall: BLACK,
+            hazard: Color::from_rgba(139, 0, 0, 255),
+            head: ORANGE,
+            body: RED,
+            tail: MAROON,
+            second_head: YELLOW,
+            second_body: BLUE,
+            second_tail: DARKBLUE,
+            fruit: GREEN,
+            poison: PURPLE,
+            golden: GOLD,
+            shield: SKYBLUE,
+            slow_mo: Color::from_rgba(245, 245, 245, 255),
+            boost: Color::from_rgba(30, 144, 255, 255),
+            rewind: Color::from_rgba(255, 105, 180, 255),
+            first_portal: Color::from_rgba(0, 120, 255, 255),
+            second_portal: Color::from_rgba(255, 140, 0, 255),
+            grid: Color { a: FAINT, ..BLACK },
+            outline: DARKBROWN,
+            text: WHITE,
+            marks: false,
+        }
+    }
+}
+
+impl Theme {
+    /// The color of `player`'s snake's head. Player one is player 0.
+    pub fn head_color(&self, player: usize) -> Color {
+        if player == 0 {
+            self.head
+        } else {
+            self.second_head
+        }
+    }
+
+    /// The color of the body segment at `index` in `player`'s snake, `length` segments long.
+    pub fn body_color(&self, player: usize, index: usize, length: usize, gradient: bool) -> Color {
+        let (body, tail) = if player == 0 {
+            (self.body, self.tail)
+        } else {
+            (self.second_body, self.second_tail)
+        };
+        if !gradient {
+            return body;
+        }
+        let t = index as f32 / length as f32;
+        Color::new(
+            body.r + (tail.r - body.r) * t,
+            body.g + (tail.g - body.g) * t,
+            body.b + (tail.b - body.b) * t,
+            body.a + (tail.a - body.a) * t,
+        )
+    }
+
+    fn dark() -> Theme {
+        Theme {
+            name: "dark",
+            background: Color::from_rgba(30, 30, 34, 255),
+            checker: Color::from_rgba(38, 38, 43, 255),
+            wall: Color::from_rgba(75, 75, 85, 255),
+            hazard: Color::from_rgba(150, 20, 20, 255),
+            head: Color::from_rgba(255, 170, 0, 255),
+            body: Color::from_rgba(200, 60, 60, 255),
+            tail: Color::from_rgba(90, 30, 40, 255),
+            second_head: Color::from_rgba(255, 240, 120, 255),
+            second_body: Color::from_rgba(60, 120, 220, 255),
+            second_tail: Color::from_rgba(30, 50, 100, 255),
+            fruit: Color::from_rgba(80, 200, 120, 255),
+            poison: Color::from_rgba(170, 90, 230, 255),
+            golden: GOLD,
+            shield: Color::from_rgba(90, 200, 255, 255),
+            slow_mo: Color::from_rgba(200, 200, 220, 255),
+            boost: Color::from_rgba(70, 150, 255, 255),
+            rewind: Color::from_rgba(255, 120, 200, 255),
+            first_portal: Color::from_rgba(40, 140, 255, 255),
+            second_portal: Color::from_rgba(255, 150, 40, 255),
+            grid: Color { a: FAINT, ..WHITE },
+            outline: Color::from_rgba(20, 20, 22, 255),
+            text: WHITE,
+            marks: false,
+        }
+    }
+
+    fn light() -> Theme {
+        Theme {
+            name: "light",
+            background: Color::from_rgba(238, 236, 226, 255),
+            checker: Color::from_rgba(228, 226, 214, 255),
+            wall: Color::from_rgba(90, 90, 96, 255),
+            hazard: Color::from_rgba(150, 30, 30, 255),
+            head: Color::from_rgba(230, 120, 0, 255),
+            body: Color::from_rgba(210, 50, 50, 255),
+            tail: Color::from_rgba(240, 150, 130, 255),
+            second_head: Color::from_rgba(240, 200, 0, 255),
+            second_body: Color::from_rgba(40, 90, 200, 255),
+            second_tail: Color::from_rgba(150, 180, 240, 255),
+            fruit: Color::from_rgba(40, 160, 60, 255),
+            poison: Color::from_rgba(140, 60, 180, 255),
+            golden: Color::from_rgba(220, 170, 0, 255),
+            shield: Color::from_rgba(30, 140, 220, 255),
+            slow_mo: Color::from_rgba(120, 120, 130, 255),
+            boost: Color::from_rgba(20, 100, 230, 255),
+            rewind: Color::from_rgba(200, 40, 140, 255),
+            first_portal: Color::from_rgba(0, 90, 220, 255),
+            second_portal: Color::from_rgba(230, 110, 0, 255),
+            grid: Color { a: FAINT, ..BLACK },
+            outline: Color::from_rgba(200, 198, 188, 255),
+            text: Color::from_rgba(20, 20, 20, 255),
+            marks: false,
+        }
+    }
+
+    /// The four colors of an old CGA monitor, plus their darker and brighter cousins.
+    fn retro() -> Theme {
+        Theme {
+            name: "retro",
+            background: BLACK,
+            checker: Color::from_rgba(24, 24, 24, 255),
+            wall: Color::from_rgba(85, 85, 85, 255),
+            hazard: Color::from_rgba(170, 0, 0, 255),
+            head: WHITE,
+            body: Color::from_rgba(85, 255, 255, 255),
+            tail: Color::from_rgba(0, 170, 170, 255),
+            second_head: Color::from_rgba(255, 255, 85, 255),
+            second_body: Color::from_rgba(85, 255, 85, 255),
+            second_tail: Color::from_rgba(0, 170, 0, 255),
+            fruit: Color::from_rgba(255, 85, 255, 255),
+            poison: Color::from_rgba(170, 0, 170, 255),
+            golden: Color::from_rgba(255, 255, 85, 255),
+            shield: Color::from_rgba(85, 85, 255, 255),
+            slow_mo: Color::from_rgba(170, 170, 170, 255),
+            boost: Color::from_rgba(85, 85, 255, 255),
+            rewind: Color::from_rgba(255, 85, 255, 255),
+            first_portal: Color::from_rgba(85, 85, 255, 255),
+            second_portal: Color::from_rgba(170, 85, 0, 255),
+            grid: Color { a: FAINT, ..WHITE },
+            outline: BLACK,
+            text: WHITE,
+            marks: false,
+        }
+    }
+
+    /// Mostly the Okabe-Ito palette, whose colors stay apart with any of the common kinds of
+    /// color blindness, plus shapes for the things that matter most.
+    fn colorblind() -> Theme {
+        Theme {
+            name: "colorblind",
+            background: Color::from_rgba(10, 10, 10, 255),
+            checker: Color::from_rgba(28, 28, 28, 255),
+            wall: Color::from_rgba(110, 110, 110, 255),
+            hazard: Color::from_rgba(213, 94, 0, 255),
+            head: Color::from_rgba(240, 228, 66, 255),
+            body: Color::from_rgba(0, 114, 178, 255),
+            tail: Color::from_rgba(0, 60, 100, 255),
+            second_head: Color::from_rgba(245, 245, 245, 255),
+            second_body: Color::from_rgba(213, 94, 0, 255),
+            second_tail: Color::from_rgba(110, 50, 0, 255),
+            fruit: Color::from_rgba(230, 159, 0, 255),
+            poison: Color::from_rgba(86, 180, 233, 255),
+            golden: Color::from_rgba(245, 245, 245, 255),
+            shield: Color::from_rgba(245, 245, 245, 255),
+            slow_mo: Color::from_rgba(180, 180, 180, 255),
+            boost: Color::from_rgba(0, 158, 115, 255),
+            rewind: Color::from_rgba(204, 121, 167, 255),
+            first_portal: Color::from_rgba(204, 121, 167, 255),
+            second_portal: Color::from_rgba(0, 158, 115, 255),
+            grid: Color { a: FAINT, ..WHITE },
+            outline: BLACK,
+            text: WHITE,
+            marks: true,
+        }
+    }
+}
+
+/// The themes that can be cycled through, and which is in use.
+pub struct Themes {
+    all: Vec<Theme>,
+    current: usize,
+    /// What `label` is worded in.
+    lang: Lang,
+    /// The menu's hint for changing theme, which names the current one.
+    pub label: String,
+    /// Whether the body fades from `body` to `tail`, rather than being all `body`.
+    pub gradient: bool,
+    /// Whether faint lines are drawn between every cell.
+    pub grid: bool,
+    /// Whether fruit gently grows and shrinks, to draw the eye.
+    pub pulse: bool,
+    /// Whether blocks have a thin `outline` around them, rather than being solid cells.
+    pub outlines: bool,
+    /// Whether the board is shaded in `background` and `checker` like a checkerboard, rather
+    /// than all `background`.
+    pub checkerboard: bool,
+    /// The names of the `UNLOCKS` that have been earned.
+    unlocked: Vec<String>,
+    /// "name: reach a score of N to unlock", for each theme that hasn't been.
+    pub locked_labels: Vec<String>,
+}
+
+impl Themes {
+    /// The built-in themes, starting with `custom` if there is one. Of the `UNLOCKS`, only
+    /// those named in `unlocked` can be used.
+    pub fn new(custom: Option<Theme>, lang: Lang, unlocked: &[String]) -> Themes {
+        let mut all = Vec::new();
+        all.extend(custom);
+        all.extend([
+            Theme::default(),
+            Theme::dark(),
+            Theme::light(),
+            Theme::retro(),
+            Theme::colorblind(),
+        ]);
+        let label = theme_label(lang, &all[0]);
+        let mut themes = Themes {
+            all,
+            current: 0,
+            lang,
+            label,
+            gradient: true,
+            grid: false,
+            pulse: true,
+            outlines: true,
+            checkerboard: false,
+            unlocked: unlocked.to_vec(),
+            locked_labels: Vec::new(),
+        };
+        themes.update_locked_labels();
+        themes
+    }
+
+    /// Catches up with the themes that have been earned, which only ever grow in number.
+    pub fn unlock(&mut self, unlocked: &[String]) {
+        if self.unlocked.len() != unlocked.len() {
+            self.unlocked = unlocked.to_vec();
+            self.update_locked_labels();
+        }
+    }
+
+    fn update_locked_labels(&mut self) {
+        self.locked_labels = UNLOCKS
+            .iter()
+            .filter(|(name, _)| !self.unlocked.iter().any(|unlocked| unlocked == name))
+            .map(|(name, score)| self.lang.fill(Text::Locked, &[name, score]))
+            .collect();
+    }
+
+    fn is_locked(&self, theme: &Theme) -> bool {
+        UNLOCKS.iter().any(|(name, _)| *name == theme.name)
+            && !self.unlocked.iter().any(|name| name == theme.name)
+    }
+
+    pub fn current(&self) -> &Theme {
+        &self.all[self.current]
+    }
+
+    /// What to outline blocks in, if they're outlined at all.
+    pub fn outline(&self) -> Option<Color> {
+        self.outlines.then_some(self.current().outline)
+    }
+
+    pub fn toggle_gradient(&mut self) {
+        self.gradient = !self.gradient;
+    }
+
+    pub fn toggle_grid(&mut self) {
+        self.grid = !self.grid;
+    }
+
+    pub fn toggle_pulse(&mut self) {
+        self.pulse = !self.pulse;
+    }
+
+    /// Moves on to the next theme that isn't locked.
+    pub fn next(&mut self) {
+        loop {
+            self.current = (self.current + 1) % self.all.len();
+            if !self.is_locked(self.current()) {
+                break;
+            }
+        }
+        self.label = theme_label(self.lang, self.current());
+    }
+
+    /// Switches to the theme called `name`, returning whether there is one and it's unlocked.
+    pub fn select(&mut self, name: &str) -> bool {
+        let index = self
+            .all
+            .iter()
+            .position(|theme| theme.name == name && !self.is_locked(theme));
+        match index {
+            Some(index) => {
+                self.current = index;
+                self.label = theme_label(self.lang, self.current());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn theme_label(lang: Lang, theme: &Theme) -> String {
+    lang.fill(Text::ChangeTheme, &[&theme.name])
+}
+
+fn custom_name() -> &'static str {
+    "custom"
+}
+
+/// Like `rgb`, but see-through, for drawing over other things.
+fn rgb_faint<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let color = rgb(deserializer)?;
+    Ok(Color { a: FAINT, ..color })
+}
+
+fn rgb<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let [r, g, b] = <[u8; 3]>::deserialize(deserializer)?;
+    Ok(Color::from_rgba(r, g, b, 255))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// How colors look with each of the three common kinds of color blindness (protanopia,
+    /// deuteranopia and tritanopia), from Machado, Oliveira and Fernandes (2009). They apply to
+    /// linear RGB.
+    const DEFICIENCIES: [[[f32; 3]; 3]; 3] = [
+        [
+            [0.152286, 1.052583, -0.204868],
+            [0.114503, 0.786281, 0.099216],
+            [-0.003882, -0.048116, 1.051998],
+        ],
+        [
+            [0.367322, 0.860646, -0.227968],
+            [0.280085, 0.672501, 0.047413],
+            [-0.011820, 0.042940, 0.968881],
+        ],
+        [
+            [1.255528, -0.076749, -0.178779],
+            [-0.078411, 0.930809, 0.147602],
+            [0.004733, 0.691367, 0.303900],
+        ],
+    ];
+
+    fn simulate(color: Color, deficiency: &[[f32; 3]; 3]) -> [f32; 3] {
+        let to_linear = |c: f32| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let from_linear = |c: f32| {
+            let c = c.clamp(0.0, 1.0);
+            if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        let linear = [color.r, color.g, color.b].map(to_linear);
+        deficiency.map(|row| from_linear((0..3).map(|i| row[i] * linear[i]).sum()))
+    }
+
+    #[test]
+    fn locked_themes_are_skipped_until_earned() {
+        let mut themes = Themes::new(None, Lang::English, &[]);
+        let mut seen = Vec::new();
+        for _ in 0..10 {
+            themes.next();
+            seen.push(themes.current().name);
+        }
+        assert!(seen.contains(&"classic") && seen.contains(&"colorblind"));
+        assert!(!seen.contains(&"dark") && !seen.contains(&"retro"));
+        assert!(!themes.select("retro"));
+        assert_eq!(themes.locked_labels.len(), UNLOCKS.len());
+
+        themes.unlock(&["dark".to_string()]);
+        assert!(themes.select("dark"));
+        assert_eq!(themes.locked_labels.len(), UNLOCKS.len() - 1);
+    }
+
+    #[test]
+    fn the_colorblind_theme_tells_things_apart_whatever_the_deficiency() {
+        let theme = Theme::colorblind();
+        let pairs = [
+            ("head", theme.head, "body", theme.body),
+            ("head", theme.head, "fruit", theme.fruit),
+            ("body", theme.body, "fruit", theme.fruit),
+            ("fruit", theme.fruit, "poison", theme.poison),
+            ("body", theme.body, "poison", theme.poison),
+            ("fruit", theme.fruit, "golden", theme.golden),
+            ("fruit", theme.fruit, "wall", theme.wall),
+            ("body", theme.body, "wall", theme.wall),
+            ("wall", theme.wall, "hazard", theme.hazard),
+            ("fruit", theme.fruit, "background", theme.background),
+            ("body", theme.body, "background", theme.background),
+            ("wall", theme.wall, "background", theme.background),
+            ("body", theme.body, "second_body", theme.second_body),
+        ];
+        for deficiency in DEFICIENCIES.iter() {
+            for (one, a, other, b) in pairs {
+                let [a, b] = [a, b].map(|color| simulate(color, deficiency));
+                let distance = (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f32>().sqrt();
+                assert!(distance > 0.25, "{} and {} look alike", one, other);
+            }
+        }
+        assert!(theme.marks);
+    }
+}
@@ -0,0 +1,113 @@
+//! Steering by touch where there's no keyboard: an on-screen D-pad in the bottom right
+//! corner, and swipes anywhere. Touches reach both as mouse presses, which macroquad turns
+//! them into.
+
+use macroquad::prelude::*;
+use snake::game::{self, Direction};
+
+/// Shown from the start on the platforms that are usually played by touch.
+pub const SHOWN_BY_DEFAULT: bool = cfg!(any(
+    target_os = "android",
+    target_os = "ios",
+    target_arch = "wasm32"
+));
+
+/// Each button's size, relative to the shorter side of the window.
+const BUTTON_SCALE: f32 = 0.1;
+/// How opaque the buttons are, so the board can be seen through them.
+const BUTTON_ALPHA: f32 = 0.35;
+/// How far a swipe has to go to turn, relative to the shorter side of the window, so a tap
+/// or a finger that wobbles doesn't.
+const MIN_SWIPE_SCALE: f32 = 0.05;
+
+pub struct DPad {
+    pub shown: bool,
+}
+
+impl DPad {
+    pub fn new(shown: bool) -> DPad {
+        DPad { shown }
+    }
+
+    /// Where each button is drawn, around a gap in the middle.
+    fn buttons() -> [(Direction, Rect); 4] {
+        let size = screen_width().min(screen_height()) * BUTTON_SCALE;
+        let center = vec2(screen_width(), screen_height()) - vec2(size, size) * 2.0;
+        let button = |offset: Vec2| {
+            let corner = center + offset * size - vec2(size, size) / 2.0;
+            Rect::new(corner.x, corner.y, size, size)
+        };
+        // Screen y points down.
+        [
+            (Direction::North, button(vec2(0.0, -1.0))),
+            (Direction::South, button(vec2(0.0, 1.0))),
+            (Direction::West, button(vec2(-1.0, 0.0))),
+            (Direction::East, button(vec2(1.0, 0.0))),
+        ]
+    }
+
+    /// The button pressed this frame, if any, while the D-pad is shown.
+    pub fn turns(&self) -> Option<Direction> {
+        if !self.shown || !is_mouse_button_pressed(MouseButton::Left) {
+            return None;
+        }
+        let pressed = Vec2::from(mouse_position());
+        DPad::buttons()
+            .into_iter()
+            .find(|(_, rect)| rect.contains(pressed))
+            .map(|(direction, _)| direction)
+    }
+
+    pub fn render(&self, color: Color) {
+        if !self.shown {
+            return;
+        }
+        let fill = Color {
+            a: BUTTON_ALPHA,
+            ..color
+        };
+        for (direction, rect) in DPad::buttons() {
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, fill);
+            // An arrow pointing the way the button turns.
+            let center = rect.center();
+            let reach = rect.w * 0.3;
+            let (tip, across) = match direction {
+                Direction::North => (vec2(0.0, -1.0), vec2(1.0, 0.0)),
+                Direction::South => (vec2(0.0, 1.0), vec2(1.0, 0.0)),
+                Direction::West => (vec2(-1.0, 0.0), vec2(0.0, 1.0)),
+                Direction::East => (vec2(1.0, 0.0), vec2(0.0, 1.0)),
+            };
+            draw_triangle(
+                center + tip * reach,
+                center - tip * reach + across * reach,
+                center - tip * reach - across * reach,
+                color,
+            );
+        }
+    }
+}
+
+/// A press dragged across the screen and let go, which turns the way it went furthest.
+#[derive(Default)]
+pub struct Swipe {
+    /// Where the press that's still held down started.
+    start: Option<Vec2>,
+}
+
+impl Swipe {
+    /// The turn swiped this frame, if a swipe ended in it. This must be called every frame, so
+    /// that presses are seen starting.
+    pub fn turns(&mut self) -> Option<Direction> {
+        let position = Vec2::from(mouse_position());
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.start = Some(position);
+        }
+        if !is_mouse_button_released(MouseButton::Left) {
+            return None;
+        }
+        let swiped = position - self.start.take()?;
+        let min_distance = screen_width().min(screen_height()) * MIN_SWIPE_SCALE;
+        // Screen y points down.
+        game::stick_to_direction(swiped.x, -swiped.y, min_distance)
+    }
+}